@@ -12,6 +12,7 @@ namespace XPBD
             _rawPtr = ptr;
         }
 
+        //Add系列方法在同类碰撞体数量超出句柄范围或参数不合法时返回uint.MaxValue
         public uint AddInfinitePlane(InfinitePlane plane)
         {
             return NativeAPI.add_infinite_plane_collider(_rawPtr, plane);
@@ -21,5 +22,72 @@ namespace XPBD
         {
             return NativeAPI.remove_infinite_plane_collider(_rawPtr, id);
         }
+
+        public uint AddSphere(Sphere sphere)
+        {
+            return NativeAPI.add_sphere_collider(_rawPtr, sphere);
+        }
+
+        public bool RemoveSphereCollider(uint id)
+        {
+            return NativeAPI.remove_sphere_collider(_rawPtr, id);
+        }
+
+        public uint AddCapsule(Capsule capsule)
+        {
+            return NativeAPI.add_capsule_collider(_rawPtr, capsule);
+        }
+
+        public bool RemoveCapsuleCollider(uint id)
+        {
+            return NativeAPI.remove_capsule_collider(_rawPtr, id);
+        }
+
+        public uint AddBox(OrientedBox box)
+        {
+            return NativeAPI.add_box_collider(_rawPtr, box);
+        }
+
+        public bool RemoveBoxCollider(uint id)
+        {
+            return NativeAPI.remove_box_collider(_rawPtr, id);
+        }
+
+        public uint AddTriangleMesh(Vector3[] vertices, int[] indices, float thickness)
+        {
+            unsafe
+            {
+                fixed (Vector3* verticesPtr = vertices)
+                fixed (int* indicesPtr = indices)
+                {
+                    return NativeAPI.add_triangle_mesh_collider(_rawPtr, (System.IntPtr)verticesPtr, (uint)vertices.Length, (System.IntPtr)indicesPtr, (uint)indices.Length, thickness);
+                }
+            }
+        }
+
+        public uint AddSdf(Vector3 origin, float cellSize, Vector3Int size, float[] distances)
+        {
+            unsafe
+            {
+                fixed (float* distancesPtr = distances)
+                {
+                    return NativeAPI.add_sdf_collider(_rawPtr, origin, cellSize, (uint)size.x, (uint)size.y, (uint)size.z, (System.IntPtr)distancesPtr);
+                }
+            }
+        }
+
+        public bool RemoveCollider(uint id)
+        {
+            return NativeAPI.remove_collider(_rawPtr, id);
+        }
+
+        public bool UpdateColliderTransform(uint id, Vector3 position, Quaternion rotation)
+        {
+            return NativeAPI.update_collider_transform(_rawPtr, id, new Pose()
+            {
+                position = position,
+                rotation = rotation,
+            });
+        }
     }
 }
@@ -3,6 +3,21 @@ using System.Collections.Generic;
 using UnityEngine;
 using System.Runtime.InteropServices;
 
+public enum BreakKind : uint
+{
+    None = 0,
+    //应变超过阈值时断裂，距离约束为相对静止长度的伸长比例
+    Strain = 1,
+    //约束力超过阈值时断裂
+    Force = 2,
+}
+
+[StructLayout(LayoutKind.Sequential)]
+public struct BreakThreshold
+{
+    public BreakKind kind;
+    public float value;
+}
 
 [StructLayout(LayoutKind.Sequential)]
 public struct DistanceConstraint
@@ -11,13 +26,56 @@ public struct DistanceConstraint
     public ulong index1;
     public float stiffnessInv;
     private float _rest;
+    public BreakThreshold breakThreshold;
+    //XPBD阻尼系数beta，0为不阻尼
+    public float damping;
 
-    public DistanceConstraint(ulong index0, ulong index1, float stiffnessInv)
+    public DistanceConstraint(ulong index0, ulong index1, float stiffnessInv, float damping = 0f)
     {
         this.index0 = index0;
         this.index1 = index1;
         this.stiffnessInv = stiffnessInv;
         _rest = 0;
+        breakThreshold = new BreakThreshold();
+        this.damping = damping;
+    }
+}
+
+//绳索约束，只阻止拉伸；maxDistance小于0时使用初始距离
+[StructLayout(LayoutKind.Sequential)]
+public struct MaxDistanceConstraint
+{
+    public ulong index0;
+    public ulong index1;
+    public float stiffnessInv;
+    public float maxDistance;
+
+    public MaxDistanceConstraint(ulong index0, ulong index1, float stiffnessInv, float maxDistance = -1f)
+    {
+        this.index0 = index0;
+        this.index1 = index1;
+        this.stiffnessInv = stiffnessInv;
+        this.maxDistance = maxDistance;
+    }
+}
+
+//距离保持在[minDistance, maxDistance]之间，范围内不产生作用；小于0时不限制该侧，两侧都限制时minDistance大于maxDistance会导致创建失败
+[StructLayout(LayoutKind.Sequential)]
+public struct RangeDistanceConstraint
+{
+    public ulong index0;
+    public ulong index1;
+    public float stiffnessInv;
+    public float minDistance;
+    public float maxDistance;
+
+    public RangeDistanceConstraint(ulong index0, ulong index1, float stiffnessInv, float minDistance, float maxDistance)
+    {
+        this.index0 = index0;
+        this.index1 = index1;
+        this.stiffnessInv = stiffnessInv;
+        this.minDistance = minDistance;
+        this.maxDistance = maxDistance;
     }
 }
 
@@ -30,4 +88,143 @@ public struct TriangleBendingConstraint
     public ulong index3;
     public float stiffnessInv;
     private float _rest;
+    public BreakThreshold breakThreshold;
+    public float damping;
+}
+
+//三角形网格弯曲模型，对所有TriangleBendingConstraint生效
+public enum BendingModel : uint
+{
+    //基于二面角，适合大变形
+    Dihedral = 0,
+    //等距弯曲，适合近似平面的布料，求解更稳定
+    Isometric = 1,
+}
+
+[StructLayout(LayoutKind.Sequential)]
+public struct TetVolumeConstraint
+{
+    public ulong index0;
+    public ulong index1;
+    public ulong index2;
+    public ulong index3;
+    public float stiffnessInv;
+    private float _rest;
+
+    public TetVolumeConstraint(ulong index0, ulong index1, ulong index2, ulong index3, float stiffnessInv)
+    {
+        this.index0 = index0;
+        this.index1 = index1;
+        this.index2 = index2;
+        this.index3 = index3;
+        this.stiffnessInv = stiffnessInv;
+        _rest = 0;
+    }
+}
+
+//Neo-Hookean有限元四面体材质，poissonRatio需小于0.5
+[StructLayout(LayoutKind.Sequential)]
+public struct NeoHookeanTet
+{
+    public ulong index0;
+    public ulong index1;
+    public ulong index2;
+    public ulong index3;
+    public float youngsModulus;
+    public float poissonRatio;
+}
+
+[StructLayout(LayoutKind.Sequential)]
+public struct WindSettings
+{
+    public Vector3 velocity;
+    public float airDensity;
+    public float dragCoefficient;
+    public float liftCoefficient;
+    public float turbulence;
+    public float turbulenceFrequency;
+
+    public static WindSettings Default(Vector3 velocity)
+    {
+        return new WindSettings()
+        {
+            velocity = velocity,
+            airDensity = 1.2f,
+            dragCoefficient = 1f,
+            liftCoefficient = 0.5f,
+            turbulence = 0f,
+            turbulenceFrequency = 1f,
+        };
+    }
+}
+
+//linear为每秒衰减的速度比例，与质点自身阻尼叠加；rigid为每秒向刚体运动靠拢的比例，按约束连通的部分分别计算
+[StructLayout(LayoutKind.Sequential)]
+public struct DampingSettings
+{
+    public float linear;
+    public float rigid;
+}
+
+public enum ForceFieldKind : uint
+{
+    Directional = 0,
+    Radial = 1,
+    Vortex = 2,
+}
+
+public enum ForceFieldFalloff : uint
+{
+    None = 0,
+    Linear = 1,
+    InverseSquare = 2,
+}
+
+//Radial的strength为负时向外排斥，Vortex绕过position、方向为direction的轴旋转；radius小于等于0时不限范围
+[StructLayout(LayoutKind.Sequential)]
+public struct ForceField
+{
+    public ForceFieldKind kind;
+    public ForceFieldFalloff falloff;
+    public Vector3 position;
+    public Vector3 direction;
+    public float strength;
+    public float radius;
+    [MarshalAs(UnmanagedType.U1)]
+    public bool isAcceleration;
+}
+
+//冲量直接改变速度，力与加速度只作用于下一次原生update
+public enum ParticleForceKind : uint
+{
+    Force = 0,
+    Acceleration = 1,
+    Impulse = 2,
+}
+
+//force为lambda / dt^2，拉伸的距离约束为负值
+[StructLayout(LayoutKind.Sequential)]
+public struct ConstraintForce
+{
+    public uint id;
+    public float force;
+}
+
+//group 0为距离约束，1为弯曲约束；newParticle为撕裂时复制出的质点，未复制时为uint.MaxValue
+[StructLayout(LayoutKind.Sequential)]
+public struct BreakEvent
+{
+    public uint group;
+    public uint id;
+    public uint p1;
+    public uint p2;
+    public uint newParticle;
+}
+
+[StructLayout(LayoutKind.Sequential)]
+public struct VolumeTriangle
+{
+    public ulong index0;
+    public ulong index1;
+    public ulong index2;
 }
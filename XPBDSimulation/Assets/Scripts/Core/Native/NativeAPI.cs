@@ -26,6 +26,38 @@ namespace XPBD
         public VecRawParts masses;
         public float bounciness;
         public float dynamicFrictionFactor;
+        public float particleRadius;
+        public VecRawParts particleRadii;
+        [MarshalAs(UnmanagedType.U1)]
+        public bool selfCollision;
+        [MarshalAs(UnmanagedType.U1)]
+        public bool selfCollisionExcludeConnected;
+        public byte substepCount;
+        public VecRawParts tetVolumeConstraints;
+        public VecRawParts volumeTriangles;
+        public float volumePressure;
+        public float volumeStiffnessInv;
+        public VecRawParts neoHookeanTets;
+        public VecRawParts shapeMatchingIndices;
+        public VecRawParts shapeMatchingClusterSizes;
+        public float shapeMatchingStiffnessInv;
+        [MarshalAs(UnmanagedType.U1)]
+        public bool splitOnBreak;
+        public VecRawParts windTriangles;
+        public DampingSettings damping;
+        public VecRawParts particleDampings;
+        [MarshalAs(UnmanagedType.U1)]
+        public bool tethers;
+        public VecRawParts maxDistanceConstraints;
+        public VecRawParts rangeDistanceConstraints;
+        //所有杆的质点索引依次排列，每根杆的质点数量由rodSizes(uint)给出
+        public VecRawParts rodIndices;
+        public VecRawParts rodSizes;
+        public float rodStretchStiffnessInv;
+        public float rodBendTwistStiffnessInv;
+        //triangleBendConstraints使用的弯曲模型，Isometric时约束位于group 8
+        public BendingModel bendingModel;
+        public float tetherStiffnessInv;
     }
 
     [StructLayout(LayoutKind.Sequential)]
@@ -35,6 +67,36 @@ namespace XPBD
         public float originToPlane;
     }
 
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Pose
+    {
+        public Vector3 position;
+        public Quaternion rotation;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Sphere
+    {
+        public Vector3 center;
+        public float radius;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Capsule
+    {
+        public Vector3 start;
+        public Vector3 end;
+        public float radius;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OrientedBox
+    {
+        public Vector3 center;
+        public Quaternion rotation;
+        public Vector3 halfExtents;
+    }
+
 
     public class NativeAPI
     {
@@ -45,29 +107,166 @@ namespace XPBD
         [DllImport("xpbd_simulation")]
         public static extern void update_xpbd_solver(IntPtr xpbdSolver);
         [DllImport("xpbd_simulation")]
+        public static extern void update_xpbd_solver_dt(IntPtr xpbdSolver, float dt);
+        [DllImport("xpbd_simulation")]
         public static extern void destroy_xpbd_solver(IntPtr xpbdSolver);
         [DllImport("xpbd_simulation")]
         public static extern void add_field_force(IntPtr xpbdSolver, Vector3 force);
         [DllImport("xpbd_simulation")]
         public static extern void add_acceleration_field(IntPtr xpbdSolver, Vector3 acc);
         [DllImport("xpbd_simulation")]
+        public static extern void set_field_force(IntPtr xpbdSolver, Vector3 force);
+        [DllImport("xpbd_simulation")]
+        public static extern void set_acceleration_field(IntPtr xpbdSolver, Vector3 acc);
+        [DllImport("xpbd_simulation")]
+        public static extern void clear_fields(IntPtr xpbdSolver);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_force_field(IntPtr xpbdSolver, ForceField field);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_force_field(IntPtr xpbdSolver, uint id, ForceField field);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool remove_force_field(IntPtr xpbdSolver, uint id);
+        [DllImport("xpbd_simulation")]
+        public static extern void clear_force_fields(IntPtr xpbdSolver);
+        [DllImport("xpbd_simulation")]
+        public static extern void set_damping(IntPtr xpbdSolver, DampingSettings settings);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_particle_damping(IntPtr xpbdSolver, uint particleIndex, float damping);
+        [DllImport("xpbd_simulation")]
         public static extern int get_particles_count(IntPtr xpbdSolver);
         [DllImport("xpbd_simulation")]
         public static extern System.IntPtr get_particles(IntPtr xpbdSolver);
         [DllImport("xpbd_simulation")]
         public static extern void attach_particle(IntPtr xpbdSolver, uint particleIndex, Vector3 position);
         [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
         public static extern bool detach_particle(IntPtr xpbdSolver, uint particleIndex);
         [DllImport("xpbd_simulation")]
         public static extern void get_position(IntPtr xpbdSolver, int index, System.IntPtr position);
         [DllImport("xpbd_simulation")]
+        public static extern uint add_rigid_sphere(IntPtr xpbdSolver, Pose pose, float mass, float radius);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_rigid_box(IntPtr xpbdSolver, Pose pose, float mass, Vector3 halfExtents);
+        [DllImport("xpbd_simulation")]
+        public static extern uint attach_particle_to_rigid_body(IntPtr xpbdSolver, uint particleIndex, uint bodyIndex, Vector3 localPoint, float stiffnessInv);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool get_rigid_body_pose(IntPtr xpbdSolver, uint bodyIndex, IntPtr pose);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_rigid_body_pose(IntPtr xpbdSolver, uint bodyIndex, Pose pose);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_rigid_body_velocity(IntPtr xpbdSolver, uint bodyIndex, Vector3 velocity, Vector3 angularVelocity);
+        [DllImport("xpbd_simulation")]
+        public static extern void set_wind(IntPtr xpbdSolver, WindSettings settings);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool apply_particle_forces(IntPtr xpbdSolver, uint kind, IntPtr indexes, IntPtr values, uint count);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool apply_particle_force_range(IntPtr xpbdSolver, uint kind, uint start, uint count, Vector3 value);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_particles(IntPtr xpbdSolver, IntPtr positions, IntPtr masses, uint count);
+        [DllImport("xpbd_simulation")]
+        public static extern void remove_particles(IntPtr xpbdSolver, IntPtr indexes, uint count, IntPtr outRemap);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_distance_constraint(IntPtr xpbdSolver, uint p1, uint p2, float stiffnessInv, float rest);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool remove_distance_constraint(IntPtr xpbdSolver, uint id);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_max_distance_constraint(IntPtr xpbdSolver, uint p1, uint p2, float stiffnessInv, float max);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool remove_max_distance_constraint(IntPtr xpbdSolver, uint id);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_triangle_bend_constraint(IntPtr xpbdSolver, uint p1, uint p2, uint p3, uint p4, float stiffnessInv, float rest);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool remove_triangle_bend_constraint(IntPtr xpbdSolver, uint id);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_isometric_bend_constraint(IntPtr xpbdSolver, uint p1, uint p2, uint p3, uint p4, float stiffnessInv);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool remove_isometric_bend_constraint(IntPtr xpbdSolver, uint id);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_distance_constraint_break_threshold(IntPtr xpbdSolver, uint id, BreakThreshold threshold);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_triangle_bend_constraint_break_threshold(IntPtr xpbdSolver, uint id, BreakThreshold threshold);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_distance_constraint_damping(IntPtr xpbdSolver, uint id, float damping);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_triangle_bend_constraint_damping(IntPtr xpbdSolver, uint id, float damping);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_isometric_bend_constraint_damping(IntPtr xpbdSolver, uint id, float damping);
+        [DllImport("xpbd_simulation")]
+        public static extern uint get_constraint_count(IntPtr xpbdSolver, uint group);
+        [DllImport("xpbd_simulation")]
+        public static extern uint copy_constraint_forces(IntPtr xpbdSolver, uint group, IntPtr forces, uint capacity);
+        [DllImport("xpbd_simulation")]
+        public static extern uint get_break_events_count(IntPtr xpbdSolver);
+        [DllImport("xpbd_simulation")]
+        public static extern IntPtr get_break_events(IntPtr xpbdSolver);
+        [DllImport("xpbd_simulation")]
+        public static extern void clear_break_events(IntPtr xpbdSolver);
+        [DllImport("xpbd_simulation")]
+        public static extern void set_split_on_break(IntPtr xpbdSolver, [MarshalAs(UnmanagedType.U1)] bool split);
+        [DllImport("xpbd_simulation")]
+        public static extern void set_tethers(IntPtr xpbdSolver, [MarshalAs(UnmanagedType.U1)] bool enabled);
+        [DllImport("xpbd_simulation")]
+        public static extern void set_tether_stiffness_inv(IntPtr xpbdSolver, float stiffnessInv);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_rod(IntPtr xpbdSolver, IntPtr indexes, uint count, float stretchStiffnessInv, float bendTwistStiffnessInv);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool set_rod_segment_fixed(IntPtr xpbdSolver, uint segmentIndex, [MarshalAs(UnmanagedType.U1)] bool fixedSegment);
+        [DllImport("xpbd_simulation")]
+        public static extern uint get_rod_segments_count(IntPtr xpbdSolver);
+        [DllImport("xpbd_simulation")]
+        public static extern uint copy_rod_orientations(IntPtr xpbdSolver, IntPtr orientations, uint capacity);
+        [DllImport("xpbd_simulation")]
         public static extern System.IntPtr get_collider_manager(IntPtr xpbdSolver);
         [DllImport("xpbd_simulation")]
         public static extern uint add_infinite_plane_collider(IntPtr colliderManager, InfinitePlane plane);
         [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
         public static extern bool remove_infinite_plane_collider(IntPtr colliderManager, uint id);
         [DllImport("xpbd_simulation")]
-        public static extern bool clear_colliders(IntPtr colliderManager);
+        public static extern uint add_sphere_collider(IntPtr colliderManager, Sphere sphere);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool remove_sphere_collider(IntPtr colliderManager, uint id);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_capsule_collider(IntPtr colliderManager, Capsule capsule);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool remove_capsule_collider(IntPtr colliderManager, uint id);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_box_collider(IntPtr colliderManager, OrientedBox box);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool remove_box_collider(IntPtr colliderManager, uint id);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_triangle_mesh_collider(IntPtr colliderManager, IntPtr vertices, uint vertexCount, IntPtr indices, uint indexCount, float thickness);
+        [DllImport("xpbd_simulation")]
+        public static extern uint add_sdf_collider(IntPtr colliderManager, Vector3 origin, float cellSize, uint sizeX, uint sizeY, uint sizeZ, IntPtr distances);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool remove_collider(IntPtr colliderManager, uint id);
+        [DllImport("xpbd_simulation")]
+        [return: MarshalAs(UnmanagedType.U1)]
+        public static extern bool update_collider_transform(IntPtr colliderManager, uint id, Pose pose);
+        [DllImport("xpbd_simulation")]
+        public static extern void clear_colliders(IntPtr colliderManager);
         [DllImport("xpbd_simulation")]
         public static extern void copy_positions(IntPtr xpbdSolver, IntPtr positionArrayPtr);
 
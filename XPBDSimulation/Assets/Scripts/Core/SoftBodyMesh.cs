@@ -29,6 +29,16 @@ public class SoftBodyMesh : MonoBehaviour
 
     [SerializeField]
     private bool _enableBendingConstraint = false;
+    [SerializeField]
+    private BendingModel _bendingModel = BendingModel.Dihedral;
+
+    //对封闭网格启用整体体积约束，形成果冻状物体
+    [SerializeField]
+    private bool _enableVolumeConstraint = false;
+    [SerializeField]
+    private float _pressure = 1;
+    [SerializeField]
+    private float _volumeFlexibility = 0;
 
     private XPBDSolver _solver;
 
@@ -70,6 +80,7 @@ public class SoftBodyMesh : MonoBehaviour
         _vertices = new NativeArray<Vector3>(_mesh.vertexCount, Allocator.Persistent, NativeArrayOptions.UninitializedMemory);
 
         _solver = this.CreateSolver(_mesh);
+        _solver.ParticleSplit += OnParticleSplit;
         if (_enableGroudCollider)
         {
 
@@ -132,6 +143,7 @@ public class SoftBodyMesh : MonoBehaviour
         }
 
         Dictionary<int, DistanceConstraint> distanceConstraintsMap = new Dictionary<int, DistanceConstraint>();
+        NativeArray<VolumeTriangle> volumeTriangles = new NativeArray<VolumeTriangle>(triangles.Length / 3, Allocator.Temp, NativeArrayOptions.UninitializedMemory);
         _edges.Clear();
         BendingConstraintBuilder bendingConstraintBuilder = new BendingConstraintBuilder()
         {
@@ -146,6 +158,12 @@ public class SoftBodyMesh : MonoBehaviour
             var b1 = _vertexToBone[v1];
             var b2 = _vertexToBone[v2];
             var b3 = _vertexToBone[v3];
+            volumeTriangles[i] = new VolumeTriangle()
+            {
+                index0 = (ulong)b1,
+                index1 = (ulong)b2,
+                index2 = (ulong)b3,
+            };
 
             var e1 = GetEdgeId(b1, b2);
             var e2 = GetEdgeId(b2, b3);
@@ -198,8 +216,13 @@ public class SoftBodyMesh : MonoBehaviour
             var bendingConstraintsList = bendingConstraintBuilder.ToBendingConstraints();
             bendingConstraints = new NativeArray<TriangleBendingConstraint>(bendingConstraintsList.ToArray(), Allocator.Temp);
             solverBuilder.FillTriangleBendingConstraints(bendingConstraints);
+            solverBuilder.bendingModel = _bendingModel;
             Debug.Log(OutputBendingConstraints(bendingConstraints));
         }
+        if (_enableVolumeConstraint)
+        {
+            solverBuilder.FillVolumeTriangles(volumeTriangles, _pressure, _volumeFlexibility);
+        }
         var solver = solverBuilder.Build();
 
         //gravity
@@ -210,6 +233,7 @@ public class SoftBodyMesh : MonoBehaviour
             _attachParticleInitOffsets.Add(positions[(int)attachParticleIndex] - transform.position);
         }
         distanceConstraints.Dispose();
+        volumeTriangles.Dispose();
         positions.Dispose();
         masses.Dispose();
         if (bendingConstraints.IsCreated)
@@ -231,6 +255,82 @@ public class SoftBodyMesh : MonoBehaviour
         }
     }
 
+    //撕裂时与原生一致：三角形中其它质点位于p1一侧时，该三角形引用p2的顶点复制一份并绑定到新质点
+    private void OnParticleSplit(BreakEvent e)
+    {
+        var source = (int)e.p2;
+        var origin = _solver.GetPosition(source);
+        var normal = _solver.GetPosition((int)e.p1) - origin;
+        var triangles = _mesh.triangles;
+        var vertexToBone = new List<int>(_vertexToBone);
+        var copies = new Dictionary<int, int>();
+        //按复制的顺序记录原顶点，新顶点依次追加在末尾
+        var copiedVertices = new List<int>();
+        for (var t = 0; t < triangles.Length; t += 3)
+        {
+            var center = Vector3.zero;
+            var hasSource = false;
+            for (var k = 0; k < 3; k++)
+            {
+                var bone = _vertexToBone[triangles[t + k]];
+                if (bone == source)
+                {
+                    hasSource = true;
+                }
+                else
+                {
+                    center += _solver.GetPosition(bone) - origin;
+                }
+            }
+            if (!hasSource || Vector3.Dot(center, normal) <= 0)
+            {
+                continue;
+            }
+            for (var k = 0; k < 3; k++)
+            {
+                var vertex = triangles[t + k];
+                if (_vertexToBone[vertex] != source)
+                {
+                    continue;
+                }
+                int copy;
+                if (!copies.TryGetValue(vertex, out copy))
+                {
+                    copy = vertexToBone.Count;
+                    vertexToBone.Add((int)e.newParticle);
+                    copies.Add(vertex, copy);
+                    copiedVertices.Add(vertex);
+                }
+                triangles[t + k] = copy;
+            }
+        }
+        if (copiedVertices.Count == 0)
+        {
+            return;
+        }
+        var positions = new List<Vector3>();
+        var uvs = new List<Vector2>();
+        _mesh.GetVertices(positions);
+        _mesh.GetUVs(0, uvs);
+        foreach (var vertex in copiedVertices)
+        {
+            positions.Add(positions[vertex]);
+            if (uvs.Count > 0)
+            {
+                uvs.Add(uvs[vertex]);
+            }
+        }
+        _mesh.SetVertices(positions);
+        if (uvs.Count > 0)
+        {
+            _mesh.SetUVs(0, uvs);
+        }
+        _mesh.triangles = triangles;
+        _vertexToBone = vertexToBone.ToArray();
+        _vertices.Dispose();
+        _vertices = new NativeArray<Vector3>(_vertexToBone.Length, Allocator.Persistent, NativeArrayOptions.UninitializedMemory);
+    }
+
     private void UpdateVertices()
     {
         for (var i = 0; i < _vertexToBone.Length; i++)
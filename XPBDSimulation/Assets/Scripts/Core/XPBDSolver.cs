@@ -13,12 +13,39 @@ namespace XPBD
         private NativeVec<TriangleBendingConstraint> _triangleBendingConstraints;
         private NativeVec<Vector3> _positions;
         private NativeVec<float> _masses;
+        private NativeVec<float> _particleRadii;
+        private NativeVec<float> _particleDampings;
+        private NativeVec<TetVolumeConstraint> _tetVolumeConstraints;
+        private NativeVec<MaxDistanceConstraint> _maxDistanceConstraints;
+        private NativeVec<RangeDistanceConstraint> _rangeDistanceConstraints;
+        private NativeVec<VolumeTriangle> _volumeTriangles;
+        private float _volumePressure = 1f;
+        private float _volumeStiffnessInv = 0f;
+        private NativeVec<NeoHookeanTet> _neoHookeanTets;
+        private NativeVec<VolumeTriangle> _windTriangles;
+        private NativeVec<ulong> _shapeMatchingIndices;
+        private NativeVec<uint> _shapeMatchingClusterSizes;
+        private float _shapeMatchingStiffnessInv = 0f;
+        private NativeVec<ulong> _rodIndices;
+        private NativeVec<uint> _rodSizes;
+        private float _rodStretchStiffnessInv = 0f;
+        private float _rodBendTwistStiffnessInv = 0f;
 
         public int iterate_count = 4;
+        public int substepCount = 1;
 
         public float bounciness = 0.5f;
         public float dynamicFrictionFactor = 0.5f;
 
+        public float particleRadius = 0f;
+        public bool selfCollision = false;
+        public bool selfCollisionExcludeConnected = true;
+        public bool splitOnBreak = false;
+        public bool tethers = false;
+        public float tetherStiffnessInv = 0f;
+        public BendingModel bendingModel = BendingModel.Dihedral;
+        public DampingSettings damping;
+
 
         private static NativeVec<T> CreateNativeVecFromNativeArray<T>(NativeArray<T> constraints) where T : unmanaged
         {
@@ -45,6 +72,67 @@ namespace XPBD
         }
 
 
+        public XPBDSolverBuilder FillTetVolumeConstraints(NativeArray<TetVolumeConstraint> constraints)
+        {
+            _tetVolumeConstraints = CreateNativeVecFromNativeArray(constraints);
+            return this;
+        }
+
+        public XPBDSolverBuilder FillMaxDistanceConstraints(NativeArray<MaxDistanceConstraint> constraints)
+        {
+            _maxDistanceConstraints = CreateNativeVecFromNativeArray(constraints);
+            return this;
+        }
+
+        //绳索、锁链等两端靠近时应松弛的连接
+        public XPBDSolverBuilder FillRangeDistanceConstraints(NativeArray<RangeDistanceConstraint> constraints)
+        {
+            _rangeDistanceConstraints = CreateNativeVecFromNativeArray(constraints);
+            return this;
+        }
+
+        public XPBDSolverBuilder FillNeoHookeanTets(NativeArray<NeoHookeanTet> tets)
+        {
+            _neoHookeanTets = CreateNativeVecFromNativeArray(tets);
+            return this;
+        }
+
+        //indices为所有簇的质点索引依次排列，clusterSizes为每个簇的质点数量，
+        //clusterSizes之和与indices数量不一致时Build失败
+        public XPBDSolverBuilder FillShapeMatchingClusters(NativeArray<ulong> indices, NativeArray<uint> clusterSizes, float stiffnessInv)
+        {
+            _shapeMatchingIndices = CreateNativeVecFromNativeArray(indices);
+            _shapeMatchingClusterSizes = CreateNativeVecFromNativeArray(clusterSizes);
+            _shapeMatchingStiffnessInv = stiffnessInv;
+            return this;
+        }
+
+        //indices依次排列每根杆的质点，rodSizes为每根杆的质点数量
+        public XPBDSolverBuilder FillRods(NativeArray<ulong> indices, NativeArray<uint> rodSizes, float stretchStiffnessInv, float bendTwistStiffnessInv)
+        {
+            _rodIndices = CreateNativeVecFromNativeArray(indices);
+            _rodSizes = CreateNativeVecFromNativeArray(rodSizes);
+            _rodStretchStiffnessInv = stretchStiffnessInv;
+            _rodBendTwistStiffnessInv = bendTwistStiffnessInv;
+            return this;
+        }
+
+        //受风力作用的三角形，风速等参数通过XPBDSolver.SetWind设置
+        public XPBDSolverBuilder FillWindTriangles(NativeArray<VolumeTriangle> triangles)
+        {
+            _windTriangles = CreateNativeVecFromNativeArray(triangles);
+            return this;
+        }
+
+        //triangles需构成法线朝外的封闭曲面
+        public XPBDSolverBuilder FillVolumeTriangles(NativeArray<VolumeTriangle> triangles, float pressure, float stiffnessInv)
+        {
+            _volumeTriangles = CreateNativeVecFromNativeArray(triangles);
+            _volumePressure = pressure;
+            _volumeStiffnessInv = stiffnessInv;
+            return this;
+        }
+
         public XPBDSolverBuilder FillPositions(NativeArray<Vector3> positions)
         {
             _positions = CreateNativeVecFromNativeArray(positions);
@@ -57,12 +145,69 @@ namespace XPBD
             return this;
         }
 
+        public XPBDSolverBuilder FillParticleRadii(NativeArray<float> radii)
+        {
+            _particleRadii = CreateNativeVecFromNativeArray(radii);
+            return this;
+        }
+
+        public XPBDSolverBuilder FillParticleDampings(NativeArray<float> dampings)
+        {
+            _particleDampings = CreateNativeVecFromNativeArray(dampings);
+            return this;
+        }
+
+        public XPBDSolverBuilder SetDamping(float linear, float rigid = 0f)
+        {
+            this.damping = new DampingSettings() { linear = linear, rigid = rigid };
+            return this;
+        }
+
+        public XPBDSolverBuilder SetParticleRadius(float radius)
+        {
+            this.particleRadius = radius;
+            return this;
+        }
+
+        public XPBDSolverBuilder SetSelfCollision(bool enable, bool excludeConnected = true)
+        {
+            this.selfCollision = enable;
+            this.selfCollisionExcludeConnected = excludeConnected;
+            return this;
+        }
+
+        //约束断裂时复制质点使网格分开，质点数量会随之增加
+        public XPBDSolverBuilder SetSplitOnBreak(bool split)
+        {
+            this.splitOnBreak = split;
+            return this;
+        }
+
+        //为Attach的质点自动生成长程连接约束，限制布料在锚点附近被拉长
+        public XPBDSolverBuilder SetTethers(bool enable)
+        {
+            this.tethers = enable;
+            return this;
+        }
+
+        public XPBDSolverBuilder SetTetherStiffnessInv(float stiffnessInv)
+        {
+            this.tetherStiffnessInv = stiffnessInv;
+            return this;
+        }
+
         public XPBDSolverBuilder SetIterateCount(int count)
         {
             this.iterate_count = count;
             return this;
         }
 
+        public XPBDSolverBuilder SetSubstepCount(int count)
+        {
+            this.substepCount = count;
+            return this;
+        }
+
         public XPBDSolverBuilder SetBounciness(float bounciness)
         {
             this.bounciness = bounciness;
@@ -84,14 +229,70 @@ namespace XPBD
                 positions = _positions.rawParts,
                 masses = _masses.rawParts,
                 iterateCount = this.iterate_count,
+                substepCount = (byte)this.substepCount,
                 bounciness = this.bounciness,
                 dynamicFrictionFactor = this.dynamicFrictionFactor,
+                particleRadius = this.particleRadius,
+                selfCollision = this.selfCollision,
+                selfCollisionExcludeConnected = this.selfCollisionExcludeConnected,
+                splitOnBreak = this.splitOnBreak,
+                tethers = this.tethers,
+                tetherStiffnessInv = this.tetherStiffnessInv,
+                bendingModel = this.bendingModel,
+                damping = this.damping,
+                volumePressure = _volumePressure,
+                volumeStiffnessInv = _volumeStiffnessInv,
+                shapeMatchingStiffnessInv = _shapeMatchingStiffnessInv,
+                rodStretchStiffnessInv = _rodStretchStiffnessInv,
+                rodBendTwistStiffnessInv = _rodBendTwistStiffnessInv,
 
             };
             if (_triangleBendingConstraints != null)
             {
                 options.bendingConstraints = _triangleBendingConstraints.rawParts;
             }
+            if (_particleRadii != null)
+            {
+                options.particleRadii = _particleRadii.rawParts;
+            }
+            if (_particleDampings != null)
+            {
+                options.particleDampings = _particleDampings.rawParts;
+            }
+            if (_tetVolumeConstraints != null)
+            {
+                options.tetVolumeConstraints = _tetVolumeConstraints.rawParts;
+            }
+            if (_maxDistanceConstraints != null)
+            {
+                options.maxDistanceConstraints = _maxDistanceConstraints.rawParts;
+            }
+            if (_rangeDistanceConstraints != null)
+            {
+                options.rangeDistanceConstraints = _rangeDistanceConstraints.rawParts;
+            }
+            if (_volumeTriangles != null)
+            {
+                options.volumeTriangles = _volumeTriangles.rawParts;
+            }
+            if (_neoHookeanTets != null)
+            {
+                options.neoHookeanTets = _neoHookeanTets.rawParts;
+            }
+            if (_windTriangles != null)
+            {
+                options.windTriangles = _windTriangles.rawParts;
+            }
+            if (_shapeMatchingIndices != null)
+            {
+                options.shapeMatchingIndices = _shapeMatchingIndices.rawParts;
+                options.shapeMatchingClusterSizes = _shapeMatchingClusterSizes.rawParts;
+            }
+            if (_rodIndices != null)
+            {
+                options.rodIndices = _rodIndices.rawParts;
+                options.rodSizes = _rodSizes.rawParts;
+            }
             return new XPBDSolver(options);
         }
     }
@@ -105,6 +306,10 @@ namespace XPBD
         internal XPBDSolver(XPBDCreateOptions options)
         {
             _ptr = NativeAPI.create_xpbd_solver(options);
+            if (_ptr == System.IntPtr.Zero)
+            {
+                throw new System.ArgumentException("invalid XPBD solver options");
+            }
             _dt = options.dt;
         }
 
@@ -130,6 +335,43 @@ namespace XPBD
             NativeAPI.add_acceleration_field(_ptr, acc);
         }
 
+        public void SetFieldForce(Vector3 force)
+        {
+            NativeAPI.set_field_force(_ptr, force);
+        }
+
+        public void SetFieldAcceleration(Vector3 acc)
+        {
+            NativeAPI.set_acceleration_field(_ptr, acc);
+        }
+
+        public void ClearFields()
+        {
+            NativeAPI.clear_fields(_ptr);
+        }
+
+        //返回的id在移除其它力场时保持不变，kind或falloff不合法时返回uint.MaxValue
+        public uint AddForceField(ForceField field)
+        {
+            return NativeAPI.add_force_field(_ptr, field);
+        }
+
+        //id不存在或kind、falloff不合法时返回false
+        public bool SetForceField(uint id, ForceField field)
+        {
+            return NativeAPI.set_force_field(_ptr, id, field);
+        }
+
+        public bool RemoveForceField(uint id)
+        {
+            return NativeAPI.remove_force_field(_ptr, id);
+        }
+
+        public void ClearForceFields()
+        {
+            NativeAPI.clear_force_fields(_ptr);
+        }
+
         public int particleCount
         {
             get
@@ -157,6 +399,321 @@ namespace XPBD
             return NativeAPI.detach_particle(_ptr, index);
         }
 
+        public uint AddRigidBox(Vector3 position, Quaternion rotation, float mass, Vector3 halfExtents)
+        {
+            var pose = new Pose() { position = position, rotation = rotation };
+            return NativeAPI.add_rigid_box(_ptr, pose, mass, halfExtents);
+        }
+
+        public uint AddRigidSphere(Vector3 position, Quaternion rotation, float mass, float radius)
+        {
+            var pose = new Pose() { position = position, rotation = rotation };
+            return NativeAPI.add_rigid_sphere(_ptr, pose, mass, radius);
+        }
+
+        //索引越界时返回uint.MaxValue，返回的连接索引在移除质点后保持不变
+        public uint AttachParticleToRigidBody(uint particleIndex, uint bodyIndex, Vector3 localPoint, float stiffnessInv = 0)
+        {
+            return NativeAPI.attach_particle_to_rigid_body(_ptr, particleIndex, bodyIndex, localPoint, stiffnessInv);
+        }
+
+        public Pose GetRigidBodyPose(uint bodyIndex)
+        {
+            Pose pose;
+            bool found;
+            unsafe
+            {
+                Pose* ptr = &pose;
+                found = NativeAPI.get_rigid_body_pose(_ptr, bodyIndex, (System.IntPtr)ptr);
+            }
+            if (!found)
+            {
+                throw new System.ArgumentOutOfRangeException(nameof(bodyIndex));
+            }
+            return pose;
+        }
+
+        //瞬移刚体，速度清零
+        public bool SetRigidBodyPose(uint bodyIndex, Vector3 position, Quaternion rotation)
+        {
+            return NativeAPI.set_rigid_body_pose(_ptr, bodyIndex, new Pose() { position = position, rotation = rotation });
+        }
+
+        //运动学刚体(质量为0)按设置的速度运动
+        public bool SetRigidBodyVelocity(uint bodyIndex, Vector3 velocity, Vector3 angularVelocity)
+        {
+            return NativeAPI.set_rigid_body_velocity(_ptr, bodyIndex, velocity, angularVelocity);
+        }
+
+        public void SetDamping(DampingSettings settings)
+        {
+            NativeAPI.set_damping(_ptr, settings);
+        }
+
+        public bool SetParticleDamping(uint index, float damping)
+        {
+            return NativeAPI.set_particle_damping(_ptr, index, damping);
+        }
+
+        public void SetWind(WindSettings settings)
+        {
+            NativeAPI.set_wind(_ptr, settings);
+        }
+
+        public void ApplyParticleForce(ParticleForceKind kind, uint index, Vector3 value)
+        {
+            ApplyParticleForceRange(kind, index, 1, value);
+        }
+
+        public void ApplyParticleForceRange(ParticleForceKind kind, uint start, uint count, Vector3 value)
+        {
+            if (!NativeAPI.apply_particle_force_range(_ptr, (uint)kind, start, count, value))
+            {
+                throw new System.ArgumentOutOfRangeException(nameof(kind));
+            }
+        }
+
+        public void ApplyParticleForces(ParticleForceKind kind, NativeArray<uint> indexes, NativeArray<Vector3> values)
+        {
+            if (indexes.Length != values.Length)
+            {
+                throw new System.ArgumentException("indexes and values must have the same length");
+            }
+            unsafe
+            {
+                var indexesPtr = Unity.Collections.LowLevel.Unsafe.NativeArrayUnsafeUtility.GetUnsafeReadOnlyPtr(indexes);
+                var valuesPtr = Unity.Collections.LowLevel.Unsafe.NativeArrayUnsafeUtility.GetUnsafeReadOnlyPtr(values);
+                if (!NativeAPI.apply_particle_forces(_ptr, (uint)kind, (System.IntPtr)indexesPtr, (System.IntPtr)valuesPtr, (uint)indexes.Length))
+                {
+                    throw new System.ArgumentOutOfRangeException(nameof(kind));
+                }
+            }
+        }
+
+        //返回第一个新质点的索引，失败时返回uint.MaxValue
+        public uint AddParticles(NativeArray<Vector3> positions, NativeArray<float> masses)
+        {
+            if (positions.Length != masses.Length)
+            {
+                throw new System.ArgumentException("positions and masses must have the same length");
+            }
+            unsafe
+            {
+                var positionsPtr = Unity.Collections.LowLevel.Unsafe.NativeArrayUnsafeUtility.GetUnsafeReadOnlyPtr(positions);
+                var massesPtr = Unity.Collections.LowLevel.Unsafe.NativeArrayUnsafeUtility.GetUnsafeReadOnlyPtr(masses);
+                return NativeAPI.add_particles(_ptr, (System.IntPtr)positionsPtr, (System.IntPtr)massesPtr, (uint)positions.Length);
+            }
+        }
+
+        private static uint RemapIndex(uint[] remap, uint index)
+        {
+            return index < remap.Length ? remap[index] : uint.MaxValue;
+        }
+
+        //剩余质点会重新编号，返回的映射表长度为移除前的质点数量，被移除的质点映射为uint.MaxValue
+        public uint[] RemoveParticles(uint[] indexes)
+        {
+            var remap = new uint[particleCount];
+            unsafe
+            {
+                fixed (uint* indexesPtr = indexes)
+                fixed (uint* remapPtr = remap)
+                {
+                    NativeAPI.remove_particles(_ptr, (System.IntPtr)indexesPtr, (uint)indexes.Length, (System.IntPtr)remapPtr);
+                }
+            }
+            var attachments = new Dictionary<uint, Vector3>();
+            foreach (var pair in _attachments)
+            {
+                if (pair.Key < remap.Length && remap[pair.Key] != uint.MaxValue)
+                {
+                    attachments.Add(remap[pair.Key], pair.Value);
+                }
+            }
+            _attachments = attachments;
+            //尚未读取的断裂事件按新的编号更新
+            for (var i = 0; i < _breakEvents.Count; i++)
+            {
+                var e = _breakEvents[i];
+                e.p1 = RemapIndex(remap, e.p1);
+                e.p2 = RemapIndex(remap, e.p2);
+                e.newParticle = RemapIndex(remap, e.newParticle);
+                _breakEvents[i] = e;
+            }
+            return remap;
+        }
+
+        //创建时传入的约束id即为其在数组中的下标，rest小于0时由当前位置计算静止值
+        public uint AddDistanceConstraint(uint p1, uint p2, float stiffnessInv, float rest = -1)
+        {
+            return NativeAPI.add_distance_constraint(_ptr, p1, p2, stiffnessInv, rest);
+        }
+
+        public bool RemoveDistanceConstraint(uint id)
+        {
+            return NativeAPI.remove_distance_constraint(_ptr, id);
+        }
+
+        public uint AddMaxDistanceConstraint(uint p1, uint p2, float stiffnessInv, float max = -1)
+        {
+            return NativeAPI.add_max_distance_constraint(_ptr, p1, p2, stiffnessInv, max);
+        }
+
+        public bool RemoveMaxDistanceConstraint(uint id)
+        {
+            return NativeAPI.remove_max_distance_constraint(_ptr, id);
+        }
+
+        public uint AddTriangleBendConstraint(uint p1, uint p2, uint p3, uint p4, float stiffnessInv, float rest = -1)
+        {
+            return NativeAPI.add_triangle_bend_constraint(_ptr, p1, p2, p3, p4, stiffnessInv, rest);
+        }
+
+        public bool RemoveTriangleBendConstraint(uint id)
+        {
+            return NativeAPI.remove_triangle_bend_constraint(_ptr, id);
+        }
+
+        //静止状态取当前质点位置，p1、p2为公共边
+        public uint AddIsometricBendConstraint(uint p1, uint p2, uint p3, uint p4, float stiffnessInv)
+        {
+            return NativeAPI.add_isometric_bend_constraint(_ptr, p1, p2, p3, p4, stiffnessInv);
+        }
+
+        public bool RemoveIsometricBendConstraint(uint id)
+        {
+            return NativeAPI.remove_isometric_bend_constraint(_ptr, id);
+        }
+
+        public bool SetDistanceConstraintBreakThreshold(uint id, BreakKind kind, float value)
+        {
+            return NativeAPI.set_distance_constraint_break_threshold(_ptr, id, new BreakThreshold() { kind = kind, value = value });
+        }
+
+        public bool SetTriangleBendConstraintBreakThreshold(uint id, BreakKind kind, float value)
+        {
+            return NativeAPI.set_triangle_bend_constraint_break_threshold(_ptr, id, new BreakThreshold() { kind = kind, value = value });
+        }
+
+        public bool SetDistanceConstraintDamping(uint id, float damping)
+        {
+            return NativeAPI.set_distance_constraint_damping(_ptr, id, damping);
+        }
+
+        public bool SetTriangleBendConstraintDamping(uint id, float damping)
+        {
+            return NativeAPI.set_triangle_bend_constraint_damping(_ptr, id, damping);
+        }
+
+        public bool SetIsometricBendConstraintDamping(uint id, float damping)
+        {
+            return NativeAPI.set_isometric_bend_constraint_damping(_ptr, id, damping);
+        }
+
+        //group 0为距离约束，1为弯曲约束，2为四面体体积约束，3为整体体积约束，4为Neo-Hookean约束，5为形状匹配约束，6为绳索约束，7为距离范围约束，8为等距弯曲约束
+        public int GetConstraintCount(uint group)
+        {
+            return (int)NativeAPI.get_constraint_count(_ptr, group);
+        }
+
+        //写入最近一个子步结束时的约束力，返回实际写入的数量
+        public int CopyConstraintForces(uint group, NativeArray<ConstraintForce> forces)
+        {
+            unsafe
+            {
+                var ptr = Unity.Collections.LowLevel.Unsafe.NativeArrayUnsafeUtility.GetUnsafePtr(forces);
+                return (int)NativeAPI.copy_constraint_forces(_ptr, group, (System.IntPtr)ptr, (uint)forces.Length);
+            }
+        }
+
+        //返回第一个新分段的索引，失败时返回-1
+        public int AddRod(NativeArray<uint> indexes, float stretchStiffnessInv, float bendTwistStiffnessInv)
+        {
+            unsafe
+            {
+                var ptr = Unity.Collections.LowLevel.Unsafe.NativeArrayUnsafeUtility.GetUnsafeReadOnlyPtr(indexes);
+                var start = NativeAPI.add_rod(_ptr, (System.IntPtr)ptr, (uint)indexes.Length, stretchStiffnessInv, bendTwistStiffnessInv);
+                return start == uint.MaxValue ? -1 : (int)start;
+            }
+        }
+
+        public bool SetRodSegmentFixed(int segmentIndex, bool fixedSegment)
+        {
+            return NativeAPI.set_rod_segment_fixed(_ptr, (uint)segmentIndex, fixedSegment);
+        }
+
+        public int GetRodSegmentCount()
+        {
+            return (int)NativeAPI.get_rod_segments_count(_ptr);
+        }
+
+        //nalgebra的四元数布局为x,y,z,w，与Unity一致
+        public int CopyRodOrientations(NativeArray<Quaternion> orientations)
+        {
+            unsafe
+            {
+                var ptr = Unity.Collections.LowLevel.Unsafe.NativeArrayUnsafeUtility.GetUnsafePtr(orientations);
+                return (int)NativeAPI.copy_rod_orientations(_ptr, (System.IntPtr)ptr, (uint)orientations.Length);
+            }
+        }
+
+        //撕裂时由p2复制出新质点newParticle，在产生断裂的原生update之后立即触发
+        public event System.Action<BreakEvent> ParticleSplit;
+
+        private List<BreakEvent> _breakEvents = new List<BreakEvent>();
+
+        //每次原生update之后读取并清除原生的断裂事件，避免固定步长循环中多次update时丢失
+        private void CollectBreakEvents()
+        {
+            var count = NativeAPI.get_break_events_count(_ptr);
+            if (count == 0)
+            {
+                return;
+            }
+            var first = _breakEvents.Count;
+            var ptr = NativeAPI.get_break_events(_ptr);
+            unsafe
+            {
+                BreakEvent* array = (BreakEvent*)ptr;
+                for (var i = 0; i < count; i++)
+                {
+                    _breakEvents.Add(array[i]);
+                }
+            }
+            NativeAPI.clear_break_events(_ptr);
+            for (var i = first; i < _breakEvents.Count; i++)
+            {
+                var e = _breakEvents[i];
+                if (e.newParticle != uint.MaxValue && ParticleSplit != null)
+                {
+                    ParticleSplit(e);
+                }
+            }
+        }
+
+        //返回上次调用以来断裂的约束
+        public void GetBreakEvents(List<BreakEvent> events)
+        {
+            events.Clear();
+            events.AddRange(_breakEvents);
+            _breakEvents.Clear();
+        }
+
+        public void SetSplitOnBreak(bool split)
+        {
+            NativeAPI.set_split_on_break(_ptr, split);
+        }
+
+        public void SetTethers(bool enable)
+        {
+            NativeAPI.set_tethers(_ptr, enable);
+        }
+
+        public void SetTetherStiffnessInv(float stiffnessInv)
+        {
+            NativeAPI.set_tether_stiffness_inv(_ptr, stiffnessInv);
+        }
+
         public ColliderManager colliderManager
         {
             get
@@ -183,11 +740,25 @@ namespace XPBD
                 }
                 _time -= this.dt;
                 NativeAPI.update_xpbd_solver(_ptr);
+                CollectBreakEvents();
                 _updateCount++;
                 // Debug.Log("udpate count = " + _updateCount);
             }
         }
 
+        //以可变的时间步长更新一次，适用于可变帧率。固定步长dt保持不变，
+        //deltaTime不为正数时native忽略本次更新
+        public void UpdateWithDeltaTime(float deltaTime)
+        {
+            foreach (var pair in _attachments)
+            {
+                NativeAPI.attach_particle(_ptr, pair.Key, pair.Value);
+            }
+            NativeAPI.update_xpbd_solver_dt(_ptr, deltaTime);
+            CollectBreakEvents();
+            _updateCount++;
+        }
+
         public float dt
         {
             get
@@ -1,13 +1,37 @@
 use std::ffi::c_void;
 
-use nalgebra::{Point3, Vector3};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
 
 use crate::{
-    collision::shapes::{InfinitePlane, ShapesManager},
-    constraints::{constraint::ConstraintsData, particle::ParticleData},
+    collision::{
+        collider::Pose,
+        sdf::SdfCollider,
+        shapes::{
+            Capsule, ColliderHandle, InfinitePlane, OrientedBox, ShapeKind, ShapesManager, Sphere,
+            INVALID_COLLIDER_HANDLE,
+        },
+        triangle_mesh::TriangleMeshCollider,
+    },
+    constraints::{
+        constraint::{BreakThreshold, Constraint, ConstraintForce, ConstraintId, ConstraintsData},
+        particle::{ParticleData, ParticleForceKind},
+    },
     constraints_impl::{
-        distance_constraint::DistanceConstraint, triangle_bend_constraint::TriangleBendConstraint,
+        distance_constraint::DistanceConstraint,
+        isometric_bend_constraint::{BendingModel, IsometricBendConstraint},
+        max_distance_constraint::MaxDistanceConstraint,
+        mesh_volume_constraint::MeshVolumeConstraint,
+        neo_hookean_constraint::{NeoHookeanTet, NeoHookeanTetConstraint},
+        range_distance_constraint::RangeDistanceConstraint,
+        shape_matching_constraint::ShapeMatchingConstraint,
+        tet_volume_constraint::TetVolumeConstraint,
+        triangle_bend_constraint::TriangleBendConstraint,
     },
+    damping::DampingSettings,
+    force_field::ForceField,
+    rigid_body::RigidBodyShape,
+    tearing::BreakEvent,
+    wind::WindSettings,
     xpbd::{XPBDBuilder, XPBD},
 };
 #[repr(C)]
@@ -32,9 +56,28 @@ fn from_raw_parts<T>(parts: &VecRawParts) -> Vec<T> {
     }
 }
 
+//只借用缓冲区的内容，不获取所有权
+fn slice_from_raw_parts<T>(parts: &VecRawParts) -> &[T] {
+    unsafe {
+        if parts.capacity == 0 {
+            return &[];
+        } else {
+            let element_size = std::mem::size_of::<T>();
+            std::slice::from_raw_parts(parts.data as *const T, parts.length as usize / element_size)
+        }
+    }
+}
+
 type XPBDSolverExportType = XPBD<(
     ConstraintsData<DistanceConstraint>,
     ConstraintsData<TriangleBendConstraint>,
+    ConstraintsData<TetVolumeConstraint>,
+    ConstraintsData<MeshVolumeConstraint>,
+    ConstraintsData<NeoHookeanTetConstraint>,
+    ConstraintsData<ShapeMatchingConstraint>,
+    ConstraintsData<MaxDistanceConstraint>,
+    ConstraintsData<RangeDistanceConstraint>,
+    ConstraintsData<IsometricBendConstraint>,
 )>;
 
 #[repr(C)]
@@ -47,6 +90,43 @@ struct XPBDCreateOptions {
     masses: VecRawParts,
     bounciness: f32,
     dynamic_friction_factor: f32,
+    particle_radius: f32,
+    particle_radii: VecRawParts,
+    self_collision: bool,
+    self_collision_exclude_connected: bool,
+    substep_count: u8,
+    tet_volume_constraints: VecRawParts,
+    //封闭曲面的三角形(每个元素为3个质点索引)，为空时不启用整体体积约束
+    volume_triangles: VecRawParts,
+    volume_pressure: f32,
+    volume_stiffness_inv: f32,
+    //Neo-Hookean有限元四面体，每个四面体展开为两个约束
+    neo_hookean_tets: VecRawParts,
+    //所有形状匹配簇的质点索引依次排列，每个簇的质点数量由cluster_sizes(u32)给出
+    shape_matching_indices: VecRawParts,
+    shape_matching_cluster_sizes: VecRawParts,
+    shape_matching_stiffness_inv: f32,
+    split_on_break: bool,
+    //受风力作用的三角形(每个元素为3个质点索引)
+    wind_triangles: VecRawParts,
+    damping: DampingSettings,
+    //每个质点的线性阻尼(f32)，为空时只使用damping.linear
+    particle_dampings: VecRawParts,
+    //为attach的质点自动生成长程连接约束
+    tethers: bool,
+    //只阻止拉伸的绳索约束
+    max_distance_constraints: VecRawParts,
+    //距离保持在[min, max]之间的约束，min或max小于0时不限制该侧
+    range_distance_constraints: VecRawParts,
+    //所有杆的质点索引依次排列，每根杆的质点数量由rod_sizes(u32)给出
+    rod_indices: VecRawParts,
+    rod_sizes: VecRawParts,
+    rod_stretch_stiffness_inv: f32,
+    rod_bend_twist_stiffness_inv: f32,
+    //triangle_bend_constraints使用的弯曲模型(BendingModel的原始值)，Isometric时转换为等距弯曲约束(group 8)
+    bending_model: u32,
+    //长程连接约束的柔度
+    tether_stiffness_inv: f32,
 }
 
 #[no_mangle]
@@ -71,27 +151,193 @@ extern "C" fn destroy_array(raw_parts: VecRawParts) {
     }
 }
 
+//按sizes将indices切分为连续的若干段，sizes之和必须等于indices的数量
+fn split_by_sizes(indices: &[usize], sizes: &[u32]) -> Option<Vec<Vec<usize>>> {
+    let mut groups = Vec::with_capacity(sizes.len());
+    let mut start = 0;
+    for size in sizes {
+        let end = start + *size as usize;
+        groups.push(indices.get(start..end)?.to_vec());
+        start = end;
+    }
+    if start != indices.len() {
+        return None;
+    }
+    return Some(groups);
+}
+
+//在获取任何缓冲区的所有权之前检查全部参数，不合法时缓冲区仍归调用者所有
+fn is_create_options_valid(options: &XPBDCreateOptions) -> bool {
+    let positions: &[Point3<f32>] = slice_from_raw_parts(&options.positions);
+    let masses: &[f32] = slice_from_raw_parts(&options.masses);
+    let particle_radii: &[f32] = slice_from_raw_parts(&options.particle_radii);
+    let particle_dampings: &[f32] = slice_from_raw_parts(&options.particle_dampings);
+    let particle_count = positions.len();
+    if masses.len() != particle_count
+        || (!particle_radii.is_empty() && particle_radii.len() != particle_count)
+        || (!particle_dampings.is_empty() && particle_dampings.len() != particle_count)
+    {
+        return false;
+    }
+    if BendingModel::from_u32(options.bending_model).is_none() {
+        return false;
+    }
+    let distance_constraints: &[DistanceConstraint] =
+        slice_from_raw_parts(&options.distance_constraints);
+    let bend_constraints: &[TriangleBendConstraint] =
+        slice_from_raw_parts(&options.triangle_bend_constraints);
+    let is_thresholds_valid = distance_constraints
+        .iter()
+        .map(|c| c.get_break_threshold())
+        .chain(bend_constraints.iter().map(|c| c.get_break_threshold()))
+        .all(|t| t.is_valid());
+    if !is_thresholds_valid {
+        return false;
+    }
+    let range_distance_constraints: &[RangeDistanceConstraint] =
+        slice_from_raw_parts(&options.range_distance_constraints);
+    //min大于max时创建失败
+    if !range_distance_constraints.iter().all(|c| c.is_valid()) {
+        return false;
+    }
+    let shape_matching_indices: &[usize] = slice_from_raw_parts(&options.shape_matching_indices);
+    let rod_indices: &[usize] = slice_from_raw_parts(&options.rod_indices);
+    //簇或杆的质点数量之和与索引数量不一致时创建失败
+    if split_by_sizes(
+        shape_matching_indices,
+        slice_from_raw_parts(&options.shape_matching_cluster_sizes),
+    )
+    .is_none()
+        || split_by_sizes(rod_indices, slice_from_raw_parts(&options.rod_sizes)).is_none()
+    {
+        return false;
+    }
+    let tet_volume_constraints: &[TetVolumeConstraint] =
+        slice_from_raw_parts(&options.tet_volume_constraints);
+    let max_distance_constraints: &[MaxDistanceConstraint] =
+        slice_from_raw_parts(&options.max_distance_constraints);
+    let volume_triangles: &[[usize; 3]] = slice_from_raw_parts(&options.volume_triangles);
+    let wind_triangles: &[[usize; 3]] = slice_from_raw_parts(&options.wind_triangles);
+    let neo_hookean_tets: &[NeoHookeanTet] = slice_from_raw_parts(&options.neo_hookean_tets);
+    let mut indexes = distance_constraints
+        .iter()
+        .flat_map(|c| c.get_pindexes())
+        .chain(bend_constraints.iter().flat_map(|c| c.get_pindexes()))
+        .chain(tet_volume_constraints.iter().flat_map(|c| c.get_pindexes()))
+        .chain(
+            max_distance_constraints
+                .iter()
+                .flat_map(|c| c.get_pindexes()),
+        )
+        .chain(
+            range_distance_constraints
+                .iter()
+                .flat_map(|c| c.get_pindexes()),
+        )
+        .chain(volume_triangles.iter().flatten())
+        .chain(wind_triangles.iter().flatten())
+        .chain(neo_hookean_tets.iter().flat_map(|t| t.p_indexes.iter()))
+        .chain(shape_matching_indices.iter())
+        .chain(rod_indices.iter());
+    return indexes.all(|&index| index < particle_count);
+}
+
+//参数不合法时返回空指针，此时不获取任何缓冲区的所有权
 #[no_mangle]
 extern "C" fn create_xpbd_solver(options: XPBDCreateOptions) -> *mut XPBDSolverExportType {
+    if !is_create_options_valid(&options) {
+        return std::ptr::null_mut();
+    }
+    //以下所有权已转移，不再提前返回
     let distance_constraints: Vec<DistanceConstraint> =
         from_raw_parts(&options.distance_constraints);
-    let bend_constraints: Vec<TriangleBendConstraint> =
+    let mut bend_constraints: Vec<TriangleBendConstraint> =
         from_raw_parts(&options.triangle_bend_constraints);
+    let bending_model = BendingModel::from_u32(options.bending_model).unwrap();
+    let mut isometric_bend_constraints = vec![];
+    if bending_model == BendingModel::Isometric {
+        isometric_bend_constraints = bend_constraints
+            .drain(..)
+            .map(|c| {
+                let mut constraint = IsometricBendConstraint::new(c.p_indexes, c.stiffness_inv);
+                constraint.set_break_threshold(c.get_break_threshold());
+                constraint.set_damping(c.get_damping());
+                constraint
+            })
+            .collect();
+    }
+    let tet_volume_constraints: Vec<TetVolumeConstraint> =
+        from_raw_parts(&options.tet_volume_constraints);
+    let volume_triangles: Vec<[usize; 3]> = from_raw_parts(&options.volume_triangles);
+    let mut mesh_volume_constraints = vec![];
+    if !volume_triangles.is_empty() {
+        mesh_volume_constraints.push(MeshVolumeConstraint::new(
+            &volume_triangles,
+            options.volume_pressure,
+            options.volume_stiffness_inv,
+        ));
+    }
+    let max_distance_constraints: Vec<MaxDistanceConstraint> =
+        from_raw_parts(&options.max_distance_constraints);
+    let range_distance_constraints: Vec<RangeDistanceConstraint> =
+        from_raw_parts(&options.range_distance_constraints);
+    let neo_hookean_tets: Vec<NeoHookeanTet> = from_raw_parts(&options.neo_hookean_tets);
+    let neo_hookean_constraints = NeoHookeanTetConstraint::from_tets(&neo_hookean_tets);
+    let shape_matching_indices: Vec<usize> = from_raw_parts(&options.shape_matching_indices);
+    let shape_matching_cluster_sizes: Vec<u32> =
+        from_raw_parts(&options.shape_matching_cluster_sizes);
+    let shape_matching_constraints: Vec<_> =
+        split_by_sizes(&shape_matching_indices, &shape_matching_cluster_sizes)
+            .unwrap()
+            .into_iter()
+            .map(|cluster| {
+                ShapeMatchingConstraint::new(cluster, options.shape_matching_stiffness_inv)
+            })
+            .collect();
+
+    let rod_indices: Vec<usize> = from_raw_parts(&options.rod_indices);
+    let rod_sizes: Vec<u32> = from_raw_parts(&options.rod_sizes);
+    let rods = split_by_sizes(&rod_indices, &rod_sizes).unwrap();
 
     // println!("bending count = {}", bend_constraints.len());
     // println!("bending = {:?}", bend_constraints[0]);
 
     let xphd = XPBDBuilder {
         dt: options.dt,
-        constraints: (distance_constraints, bend_constraints),
+        constraints: (
+            distance_constraints,
+            bend_constraints,
+            tet_volume_constraints,
+            mesh_volume_constraints,
+            neo_hookean_constraints,
+            shape_matching_constraints,
+            max_distance_constraints,
+            range_distance_constraints,
+            isometric_bend_constraints,
+        ),
         positions: from_raw_parts(&options.positions),
         masses: from_raw_parts(&options.masses),
         iterate_count: options.constraints_solve_iter_count,
+        substep_count: options.substep_count,
         bounciness: options.bounciness,
         dynamic_friction_factor: options.dynamic_friction_factor,
+        particle_radius: options.particle_radius,
+        particle_radii: from_raw_parts(&options.particle_radii),
+        self_collision: options.self_collision,
+        self_collision_exclude_connected: options.self_collision_exclude_connected,
+        split_on_break: options.split_on_break,
+        wind_triangles: from_raw_parts(&options.wind_triangles),
+        damping: options.damping,
+        particle_dampings: from_raw_parts(&options.particle_dampings),
+        tethers: options.tethers,
+        tether_stiffness_inv: options.tether_stiffness_inv,
+        rods: rods,
+        rod_stretch_stiffness_inv: options.rod_stretch_stiffness_inv,
+        rod_bend_twist_stiffness_inv: options.rod_bend_twist_stiffness_inv,
     }
     .build();
-    return Box::into_raw(Box::new(xphd));
+    //build的检查已由is_create_options_valid提前完成
+    return Box::into_raw(Box::new(xphd.unwrap()));
 }
 
 #[no_mangle]
@@ -100,6 +346,12 @@ extern "C" fn update_xpbd_solver(ptr: *mut XPBDSolverExportType) {
     xpbd.update();
 }
 
+#[no_mangle]
+extern "C" fn update_xpbd_solver_dt(ptr: *mut XPBDSolverExportType, dt: f32) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.update_with_dt(dt);
+}
+
 #[no_mangle]
 extern "C" fn destroy_xpbd_solver(ptr: *mut XPBDSolverExportType) {
     unsafe {
@@ -119,6 +371,119 @@ extern "C" fn add_acceleration_field(ptr: *mut XPBDSolverExportType, acc: Vector
     xpbd.add_acceleration_field(acc);
 }
 
+#[no_mangle]
+extern "C" fn set_field_force(ptr: *mut XPBDSolverExportType, force: Vector3<f32>) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.set_field_force(force);
+}
+
+#[no_mangle]
+extern "C" fn set_acceleration_field(ptr: *mut XPBDSolverExportType, acc: Vector3<f32>) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.set_acceleration_field(acc);
+}
+
+#[no_mangle]
+extern "C" fn clear_fields(ptr: *mut XPBDSolverExportType) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.clear_fields();
+}
+
+//kind或falloff不合法时返回u32::MAX
+#[no_mangle]
+extern "C" fn add_force_field(ptr: *mut XPBDSolverExportType, field: ForceField) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return match xpbd.add_force_field(field) {
+        Some(id) => id as u32,
+        None => u32::MAX,
+    };
+}
+
+//id不存在或kind、falloff不合法时返回false
+#[no_mangle]
+extern "C" fn set_force_field(ptr: *mut XPBDSolverExportType, id: u32, field: ForceField) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.set_force_field(id as usize, field);
+}
+
+#[no_mangle]
+extern "C" fn remove_force_field(ptr: *mut XPBDSolverExportType, id: u32) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.remove_force_field(id as usize);
+}
+
+#[no_mangle]
+extern "C" fn clear_force_fields(ptr: *mut XPBDSolverExportType) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.clear_force_fields();
+}
+
+//kind: 0为力，1为加速度，2为冲量；力与加速度只作用于下一次update，kind无效时返回false
+#[no_mangle]
+extern "C" fn apply_particle_forces(
+    ptr: *mut XPBDSolverExportType,
+    kind: u32,
+    indexes: *const u32,
+    values: *const Vector3<f32>,
+    count: u32,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let kind = match ParticleForceKind::from_u32(kind) {
+        Some(kind) => kind,
+        None => return false,
+    };
+    if indexes.is_null() || values.is_null() {
+        return count == 0;
+    }
+    let indexes = unsafe { std::slice::from_raw_parts(indexes, count as usize) };
+    let values = unsafe { std::slice::from_raw_parts(values, count as usize) };
+    for (index, value) in indexes.iter().zip(values) {
+        xpbd.apply_particle_force(kind, *index as usize, value);
+    }
+    return true;
+}
+
+//kind无效时返回false
+#[no_mangle]
+extern "C" fn apply_particle_force_range(
+    ptr: *mut XPBDSolverExportType,
+    kind: u32,
+    start: u32,
+    count: u32,
+    value: Vector3<f32>,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let kind = match ParticleForceKind::from_u32(kind) {
+        Some(kind) => kind,
+        None => return false,
+    };
+    let start = start as usize;
+    xpbd.apply_particle_force_range(kind, start..start + count as usize, &value);
+    return true;
+}
+
+#[no_mangle]
+extern "C" fn set_wind(ptr: *mut XPBDSolverExportType, settings: WindSettings) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.set_wind(settings);
+}
+
+#[no_mangle]
+extern "C" fn set_damping(ptr: *mut XPBDSolverExportType, settings: DampingSettings) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.set_damping(settings);
+}
+
+#[no_mangle]
+extern "C" fn set_particle_damping(
+    ptr: *mut XPBDSolverExportType,
+    particle_index: u32,
+    damping: f32,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.set_particle_damping(particle_index as usize, damping);
+}
+
 #[no_mangle]
 extern "C" fn get_particles_count(ptr: *mut XPBDSolverExportType) -> i32 {
     let xpbd = unsafe { ptr.as_mut().unwrap() };
@@ -157,6 +522,489 @@ extern "C" fn get_position(ptr: *mut XPBDSolverExportType, index: i32, pos: *mut
     }
 }
 
+#[no_mangle]
+extern "C" fn add_rigid_sphere(
+    ptr: *mut XPBDSolverExportType,
+    pose: Pose,
+    mass: f32,
+    radius: f32,
+) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.add_rigid_body(pose, mass, RigidBodyShape::Sphere { radius: radius }) as u32;
+}
+
+#[no_mangle]
+extern "C" fn add_rigid_box(
+    ptr: *mut XPBDSolverExportType,
+    pose: Pose,
+    mass: f32,
+    half_extents: Vector3<f32>,
+) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let shape = RigidBodyShape::Box {
+        half_extents: half_extents,
+    };
+    return xpbd.add_rigid_body(pose, mass, shape) as u32;
+}
+
+//索引越界时返回u32::MAX
+#[no_mangle]
+extern "C" fn attach_particle_to_rigid_body(
+    ptr: *mut XPBDSolverExportType,
+    particle_index: u32,
+    body_index: u32,
+    local_point: Point3<f32>,
+    stiffness_inv: f32,
+) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return match xpbd.attach_to_rigid_body(
+        particle_index as usize,
+        body_index as usize,
+        local_point,
+        stiffness_inv,
+    ) {
+        Some(index) => index as u32,
+        None => u32::MAX,
+    };
+}
+
+//索引越界或pose为空时返回false
+#[no_mangle]
+extern "C" fn get_rigid_body_pose(
+    ptr: *mut XPBDSolverExportType,
+    index: u32,
+    pose: *mut Pose,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let index = index as usize;
+    if pose.is_null() || index >= xpbd.rigid_body_count() {
+        return false;
+    }
+    unsafe {
+        *pose = xpbd.rigid_body(index).pose();
+    }
+    return true;
+}
+
+#[no_mangle]
+extern "C" fn set_rigid_body_pose(ptr: *mut XPBDSolverExportType, index: u32, pose: Pose) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.set_rigid_body_pose(index as usize, pose);
+}
+
+#[no_mangle]
+extern "C" fn set_rigid_body_velocity(
+    ptr: *mut XPBDSolverExportType,
+    index: u32,
+    velocity: Vector3<f32>,
+    angular_velocity: Vector3<f32>,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.set_rigid_body_velocity(index as usize, velocity, angular_velocity);
+}
+
+//在末尾加入count个质点，返回第一个新质点的索引，失败时返回u32::MAX
+#[no_mangle]
+extern "C" fn add_particles(
+    ptr: *mut XPBDSolverExportType,
+    positions: *const Point3<f32>,
+    masses: *const f32,
+    count: u32,
+) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    if count == 0 || positions.is_null() || masses.is_null() {
+        return u32::MAX;
+    }
+    let positions = unsafe { std::slice::from_raw_parts(positions, count as usize) };
+    let masses = unsafe { std::slice::from_raw_parts(masses, count as usize) };
+    return match xpbd.add_particles(positions.to_vec(), masses.to_vec()) {
+        Ok(first) => first as u32,
+        Err(_) => u32::MAX,
+    };
+}
+
+//移除质点后剩余质点会重新编号，out_remap需能容纳移除前的质点数量，
+//写入旧索引对应的新索引，被移除的质点为u32::MAX
+#[no_mangle]
+extern "C" fn remove_particles(
+    ptr: *mut XPBDSolverExportType,
+    indexes: *const u32,
+    count: u32,
+    out_remap: *mut u32,
+) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    if indexes.is_null() {
+        return;
+    }
+    let indexes = unsafe { std::slice::from_raw_parts(indexes, count as usize) };
+    let indexes: Vec<usize> = indexes.iter().map(|i| *i as usize).collect();
+    let remap = xpbd.remove_particles(&indexes);
+    if out_remap.is_null() {
+        return;
+    }
+    let out_remap = unsafe { std::slice::from_raw_parts_mut(out_remap, remap.len()) };
+    for (out, new_index) in out_remap.iter_mut().zip(remap) {
+        *out = new_index.map_or(u32::MAX, |i| i as u32);
+    }
+}
+
+//rest小于0时由当前质点位置计算静止长度，质点索引越界时返回u32::MAX
+#[no_mangle]
+extern "C" fn add_distance_constraint(
+    ptr: *mut XPBDSolverExportType,
+    p1: u32,
+    p2: u32,
+    stiffness_inv: f32,
+    rest: f32,
+) -> ConstraintId {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let count = xpbd.particles_count();
+    let (p1, p2) = (p1 as usize, p2 as usize);
+    if p1 >= count || p2 >= count {
+        return u32::MAX;
+    }
+    let mut constraint = DistanceConstraint::with_rest(p1, p2, stiffness_inv, rest);
+    if rest < 0.0 {
+        constraint.on_initialize(xpbd.particles_data());
+    }
+    let id = xpbd.constraints_mut().0.push(constraint);
+    xpbd.mark_topology_dirty();
+    return id;
+}
+
+#[no_mangle]
+extern "C" fn remove_distance_constraint(ptr: *mut XPBDSolverExportType, id: ConstraintId) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let removed = xpbd.constraints_mut().0.remove_by_id(id).is_some();
+    if removed {
+        xpbd.mark_topology_dirty();
+    }
+    return removed;
+}
+
+//rest小于0时由当前质点位置计算静止二面角，质点索引越界时返回u32::MAX
+#[no_mangle]
+extern "C" fn add_triangle_bend_constraint(
+    ptr: *mut XPBDSolverExportType,
+    p1: u32,
+    p2: u32,
+    p3: u32,
+    p4: u32,
+    stiffness_inv: f32,
+    rest: f32,
+) -> ConstraintId {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let count = xpbd.particles_count();
+    let p_indexes = [p1 as usize, p2 as usize, p3 as usize, p4 as usize];
+    if p_indexes.iter().any(|i| *i >= count) {
+        return u32::MAX;
+    }
+    let mut constraint = TriangleBendConstraint::with_rest(p_indexes, stiffness_inv, rest);
+    if rest < 0.0 {
+        constraint.on_initialize(xpbd.particles_data());
+    }
+    let id = xpbd.constraints_mut().1.push(constraint);
+    xpbd.mark_topology_dirty();
+    return id;
+}
+
+#[no_mangle]
+extern "C" fn remove_triangle_bend_constraint(
+    ptr: *mut XPBDSolverExportType,
+    id: ConstraintId,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let removed = xpbd.constraints_mut().1.remove_by_id(id).is_some();
+    if removed {
+        xpbd.mark_topology_dirty();
+    }
+    return removed;
+}
+
+//静止状态取当前质点位置，质点索引越界时返回u32::MAX
+#[no_mangle]
+extern "C" fn add_isometric_bend_constraint(
+    ptr: *mut XPBDSolverExportType,
+    p1: u32,
+    p2: u32,
+    p3: u32,
+    p4: u32,
+    stiffness_inv: f32,
+) -> ConstraintId {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let count = xpbd.particles_count();
+    let p_indexes = [p1 as usize, p2 as usize, p3 as usize, p4 as usize];
+    if p_indexes.iter().any(|i| *i >= count) {
+        return u32::MAX;
+    }
+    let mut constraint = IsometricBendConstraint::new(p_indexes, stiffness_inv);
+    constraint.on_initialize(xpbd.particles_data());
+    let id = xpbd.constraints_mut().8.push(constraint);
+    xpbd.mark_topology_dirty();
+    return id;
+}
+
+#[no_mangle]
+extern "C" fn remove_isometric_bend_constraint(
+    ptr: *mut XPBDSolverExportType,
+    id: ConstraintId,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let removed = xpbd.constraints_mut().8.remove_by_id(id).is_some();
+    if removed {
+        xpbd.mark_topology_dirty();
+    }
+    return removed;
+}
+
+//max小于0时由当前质点位置计算最大距离，质点索引越界时返回u32::MAX
+#[no_mangle]
+extern "C" fn add_max_distance_constraint(
+    ptr: *mut XPBDSolverExportType,
+    p1: u32,
+    p2: u32,
+    stiffness_inv: f32,
+    max: f32,
+) -> ConstraintId {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let count = xpbd.particles_count();
+    let (p1, p2) = (p1 as usize, p2 as usize);
+    if p1 >= count || p2 >= count {
+        return u32::MAX;
+    }
+    let mut constraint = MaxDistanceConstraint::with_max(p1, p2, stiffness_inv, max);
+    constraint.on_initialize(xpbd.particles_data());
+    let id = xpbd.constraints_mut().6.push(constraint);
+    xpbd.mark_topology_dirty();
+    return id;
+}
+
+#[no_mangle]
+extern "C" fn remove_max_distance_constraint(
+    ptr: *mut XPBDSolverExportType,
+    id: ConstraintId,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let removed = xpbd.constraints_mut().6.remove_by_id(id).is_some();
+    if removed {
+        xpbd.mark_topology_dirty();
+    }
+    return removed;
+}
+
+//threshold.kind不合法或id不存在时返回false
+#[no_mangle]
+extern "C" fn set_distance_constraint_break_threshold(
+    ptr: *mut XPBDSolverExportType,
+    id: ConstraintId,
+    threshold: BreakThreshold,
+) -> bool {
+    if !threshold.is_valid() {
+        return false;
+    }
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let constraints = &mut xpbd.constraints_mut().0;
+    return match constraints.index_of(id) {
+        Some(index) => {
+            constraints.defines[index].set_break_threshold(threshold);
+            true
+        }
+        None => false,
+    };
+}
+
+//threshold.kind不合法或id不存在时返回false
+#[no_mangle]
+extern "C" fn set_triangle_bend_constraint_break_threshold(
+    ptr: *mut XPBDSolverExportType,
+    id: ConstraintId,
+    threshold: BreakThreshold,
+) -> bool {
+    if !threshold.is_valid() {
+        return false;
+    }
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let constraints = &mut xpbd.constraints_mut().1;
+    return match constraints.index_of(id) {
+        Some(index) => {
+            constraints.defines[index].set_break_threshold(threshold);
+            true
+        }
+        None => false,
+    };
+}
+
+#[no_mangle]
+extern "C" fn set_distance_constraint_damping(
+    ptr: *mut XPBDSolverExportType,
+    id: ConstraintId,
+    damping: f32,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let constraints = &mut xpbd.constraints_mut().0;
+    return match constraints.index_of(id) {
+        Some(index) => {
+            constraints.defines[index].set_damping(damping);
+            constraints.mark_cache_dirty_at(index);
+            true
+        }
+        None => false,
+    };
+}
+
+#[no_mangle]
+extern "C" fn set_triangle_bend_constraint_damping(
+    ptr: *mut XPBDSolverExportType,
+    id: ConstraintId,
+    damping: f32,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let constraints = &mut xpbd.constraints_mut().1;
+    return match constraints.index_of(id) {
+        Some(index) => {
+            constraints.defines[index].set_damping(damping);
+            constraints.mark_cache_dirty_at(index);
+            true
+        }
+        None => false,
+    };
+}
+
+#[no_mangle]
+extern "C" fn set_isometric_bend_constraint_damping(
+    ptr: *mut XPBDSolverExportType,
+    id: ConstraintId,
+    damping: f32,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    let constraints = &mut xpbd.constraints_mut().8;
+    return match constraints.index_of(id) {
+        Some(index) => {
+            constraints.defines[index].set_damping(damping);
+            constraints.mark_cache_dirty_at(index);
+            true
+        }
+        None => false,
+    };
+}
+
+//group 0为距离约束，1为弯曲约束，2为四面体体积约束，依此类推，8为等距弯曲约束
+#[no_mangle]
+extern "C" fn get_constraint_count(ptr: *mut XPBDSolverExportType, group: u32) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.constraint_count(group) as u32;
+}
+
+//forces的长度为capacity，返回实际写入的数量
+#[no_mangle]
+extern "C" fn copy_constraint_forces(
+    ptr: *mut XPBDSolverExportType,
+    group: u32,
+    forces: *mut ConstraintForce,
+    capacity: u32,
+) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    if forces.is_null() {
+        return 0;
+    }
+    let forces = unsafe { std::slice::from_raw_parts_mut(forces, capacity as usize) };
+    return xpbd.copy_constraint_forces(group, forces) as u32;
+}
+
+//自上次clear_break_events以来断裂的约束，group 0为距离约束，1为弯曲约束
+#[no_mangle]
+extern "C" fn get_break_events_count(ptr: *mut XPBDSolverExportType) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.break_events().len() as u32;
+}
+
+#[no_mangle]
+extern "C" fn get_break_events(ptr: *mut XPBDSolverExportType) -> *const BreakEvent {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.break_events().as_ptr();
+}
+
+//读取断裂事件后调用，未清除的事件在多次update间累积
+#[no_mangle]
+extern "C" fn clear_break_events(ptr: *mut XPBDSolverExportType) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.clear_break_events();
+}
+
+#[no_mangle]
+extern "C" fn set_split_on_break(ptr: *mut XPBDSolverExportType, split: bool) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.set_split_on_break(split);
+}
+
+#[no_mangle]
+extern "C" fn set_tethers(ptr: *mut XPBDSolverExportType, enabled: bool) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.set_tethers(enabled);
+}
+
+#[no_mangle]
+extern "C" fn set_tether_stiffness_inv(ptr: *mut XPBDSolverExportType, stiffness_inv: f32) {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    xpbd.set_tether_stiffness_inv(stiffness_inv);
+}
+
+//返回第一个新分段的索引，质点索引越界时返回u32::MAX
+#[no_mangle]
+extern "C" fn add_rod(
+    ptr: *mut XPBDSolverExportType,
+    indexes: *const u32,
+    count: u32,
+    stretch_stiffness_inv: f32,
+    bend_twist_stiffness_inv: f32,
+) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    if indexes.is_null() {
+        return u32::MAX;
+    }
+    let indexes = unsafe { std::slice::from_raw_parts(indexes, count as usize) };
+    let indexes: Vec<usize> = indexes.iter().map(|i| *i as usize).collect();
+    return match xpbd.add_rod(&indexes, stretch_stiffness_inv, bend_twist_stiffness_inv) {
+        Some(range) => range.start as u32,
+        None => u32::MAX,
+    };
+}
+
+#[no_mangle]
+extern "C" fn set_rod_segment_fixed(
+    ptr: *mut XPBDSolverExportType,
+    segment_index: u32,
+    fixed: bool,
+) -> bool {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.set_rod_segment_fixed(segment_index as usize, fixed);
+}
+
+#[no_mangle]
+extern "C" fn get_rod_segments_count(ptr: *mut XPBDSolverExportType) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    return xpbd.rod_segments().len() as u32;
+}
+
+//orientations的长度为capacity，返回实际写入的数量
+#[no_mangle]
+extern "C" fn copy_rod_orientations(
+    ptr: *mut XPBDSolverExportType,
+    orientations: *mut UnitQuaternion<f32>,
+    capacity: u32,
+) -> u32 {
+    let xpbd = unsafe { ptr.as_mut().unwrap() };
+    if orientations.is_null() {
+        return 0;
+    }
+    let orientations = unsafe { std::slice::from_raw_parts_mut(orientations, capacity as usize) };
+    let segments = xpbd.rod_segments();
+    for (out, segment) in orientations.iter_mut().zip(segments) {
+        *out = segment.orientation;
+    }
+    return segments.len().min(capacity as usize) as u32;
+}
+
 #[no_mangle]
 extern "C" fn get_collider_manager(ptr: *mut XPBDSolverExportType) -> *mut ShapesManager {
     let xpbd = unsafe { ptr.as_mut().unwrap() };
@@ -164,15 +1012,127 @@ extern "C" fn get_collider_manager(ptr: *mut XPBDSolverExportType) -> *mut Shape
 }
 
 #[no_mangle]
-extern "C" fn add_infinite_plane_collider(ptr: *mut ShapesManager, shape: InfinitePlane) -> u32 {
+extern "C" fn add_infinite_plane_collider(
+    ptr: *mut ShapesManager,
+    shape: InfinitePlane,
+) -> ColliderHandle {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    return shapes_manager.add_infinite_plane(shape);
+}
+
+#[no_mangle]
+extern "C" fn remove_infinite_plane_collider(ptr: *mut ShapesManager, id: ColliderHandle) -> bool {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    return shapes_manager.remove_of_kind(ShapeKind::InfinitePlane, id);
+}
+
+#[no_mangle]
+extern "C" fn add_sphere_collider(ptr: *mut ShapesManager, shape: Sphere) -> ColliderHandle {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    return shapes_manager.add_sphere(shape);
+}
+
+#[no_mangle]
+extern "C" fn remove_sphere_collider(ptr: *mut ShapesManager, id: ColliderHandle) -> bool {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    return shapes_manager.remove_of_kind(ShapeKind::Sphere, id);
+}
+
+#[no_mangle]
+extern "C" fn add_capsule_collider(ptr: *mut ShapesManager, shape: Capsule) -> ColliderHandle {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    return shapes_manager.add_capsule(shape);
+}
+
+#[no_mangle]
+extern "C" fn remove_capsule_collider(ptr: *mut ShapesManager, id: ColliderHandle) -> bool {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    return shapes_manager.remove_of_kind(ShapeKind::Capsule, id);
+}
+
+#[no_mangle]
+extern "C" fn add_box_collider(ptr: *mut ShapesManager, shape: OrientedBox) -> ColliderHandle {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    return shapes_manager.add_box(shape);
+}
+
+#[no_mangle]
+extern "C" fn remove_box_collider(ptr: *mut ShapesManager, id: ColliderHandle) -> bool {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    return shapes_manager.remove_of_kind(ShapeKind::Box, id);
+}
+
+//顶点与索引数据会被拷贝，调用后即可释放。
+//指针为空、index_count不是3的倍数或索引越界时返回u32::MAX
+#[no_mangle]
+extern "C" fn add_triangle_mesh_collider(
+    ptr: *mut ShapesManager,
+    vertices: *const Point3<f32>,
+    vertex_count: u32,
+    indices: *const u32,
+    index_count: u32,
+    thickness: f32,
+) -> ColliderHandle {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    if vertices.is_null() || indices.is_null() || index_count % 3 != 0 {
+        return INVALID_COLLIDER_HANDLE;
+    }
+    let vertices = unsafe { std::slice::from_raw_parts(vertices, vertex_count as usize) };
+    let indices = unsafe { std::slice::from_raw_parts(indices, index_count as usize) };
+    let triangles = indices
+        .chunks_exact(3)
+        .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+        .collect();
+    return match TriangleMeshCollider::new(vertices.to_vec(), triangles, thickness) {
+        Ok(mesh) => shapes_manager.add_triangle_mesh(mesh),
+        Err(_) => INVALID_COLLIDER_HANDLE,
+    };
+}
+
+//distances按x优先、z最后的顺序存放，数据会被拷贝。参数不合法时返回u32::MAX
+#[no_mangle]
+extern "C" fn add_sdf_collider(
+    ptr: *mut ShapesManager,
+    origin: Point3<f32>,
+    cell_size: f32,
+    size_x: u32,
+    size_y: u32,
+    size_z: u32,
+    distances: *const f32,
+) -> ColliderHandle {
+    let shapes_manager = unsafe { ptr.as_mut().unwrap() };
+    if distances.is_null() {
+        return INVALID_COLLIDER_HANDLE;
+    }
+    let dims = [size_x as usize, size_y as usize, size_z as usize];
+    let count = match dims[0]
+        .checked_mul(dims[1])
+        .and_then(|c| c.checked_mul(dims[2]))
+    {
+        Some(count) => count,
+        None => return INVALID_COLLIDER_HANDLE,
+    };
+    let distances = unsafe { std::slice::from_raw_parts(distances, count) };
+    return match SdfCollider::new(origin, cell_size, dims, distances.to_vec()) {
+        Ok(sdf) => shapes_manager.add_sdf(sdf),
+        Err(_) => INVALID_COLLIDER_HANDLE,
+    };
+}
+
+#[no_mangle]
+extern "C" fn remove_collider(ptr: *mut ShapesManager, id: ColliderHandle) -> bool {
     let shapes_manager = unsafe { ptr.as_mut().unwrap() };
-    return shapes_manager.infinite_planes.add(shape) as u32;
+    return shapes_manager.remove(id);
 }
 
 #[no_mangle]
-extern "C" fn remove_infinite_plane_collider(ptr: *mut ShapesManager, id: u32) -> bool {
+extern "C" fn update_collider_transform(
+    ptr: *mut ShapesManager,
+    id: ColliderHandle,
+    pose: Pose,
+) -> bool {
     let shapes_manager = unsafe { ptr.as_mut().unwrap() };
-    return shapes_manager.infinite_planes.remove(id as usize).is_some();
+    return shapes_manager.set_pose(id, pose);
 }
 
 #[no_mangle]
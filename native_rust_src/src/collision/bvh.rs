@@ -0,0 +1,143 @@
+use nalgebra::{Point3, Vector3};
+
+const LEAF_SIZE: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    pub fn empty() -> Aabb {
+        return Aabb {
+            min: point![f32::MAX, f32::MAX, f32::MAX],
+            max: point![f32::MIN, f32::MIN, f32::MIN],
+        };
+    }
+
+    pub fn grow(&mut self, p: &Point3<f32>) {
+        self.min = self.min.inf(p);
+        self.max = self.max.sup(p);
+    }
+
+    pub fn merge(&mut self, other: &Aabb) {
+        self.grow(&other.min);
+        self.grow(&other.max);
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        return nalgebra::center(&self.min, &self.max);
+    }
+
+    pub fn distance_squared(&self, p: &Point3<f32>) -> f32 {
+        let d: Vector3<f32> = (self.min - p).sup(&(p - self.max)).sup(&Vector3::zeros());
+        return d.dot(&d);
+    }
+}
+
+struct BvhNode {
+    aabb: Aabb,
+    //叶子节点: 图元区间[start, start + count)；内部节点: count为0, start为右子节点索引，左子节点紧随其后
+    start: usize,
+    count: usize,
+}
+
+//包围盒层次树，只负责空间划分，图元由调用方根据索引自行访问
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    primitives: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn build(aabbs: &[Aabb]) -> Bvh {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(aabbs.len() * 2),
+            primitives: (0..aabbs.len()).collect(),
+        };
+        if !aabbs.is_empty() {
+            bvh.build_node(aabbs, 0, aabbs.len());
+        }
+        return bvh;
+    }
+
+    fn build_node(&mut self, aabbs: &[Aabb], start: usize, end: usize) -> usize {
+        let mut aabb = Aabb::empty();
+        let mut centers = Aabb::empty();
+        for &prim in &self.primitives[start..end] {
+            aabb.merge(&aabbs[prim]);
+            centers.grow(&aabbs[prim].center());
+        }
+        let node_index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            aabb: aabb,
+            start: start,
+            count: end - start,
+        });
+        if end - start <= LEAF_SIZE {
+            return node_index;
+        }
+        //沿包围盒最长轴按中位数划分
+        let extent = centers.max - centers.min;
+        let axis = extent.imax();
+        let mid = (start + end) / 2;
+        self.primitives[start..end].select_nth_unstable_by(mid - start, |a, b| {
+            let ca = aabbs[*a].center()[axis];
+            let cb = aabbs[*b].center()[axis];
+            ca.partial_cmp(&cb).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.build_node(aabbs, start, mid);
+        let right = self.build_node(aabbs, mid, end);
+        self.nodes[node_index].start = right;
+        self.nodes[node_index].count = 0;
+        return node_index;
+    }
+
+    //查找距离p最近的图元。distance_squared(prim)返回p到图元的距离平方，
+    //只考虑距离平方小于max_distance_squared的图元
+    pub fn find_nearest<F>(
+        &self,
+        p: &Point3<f32>,
+        max_distance_squared: f32,
+        mut distance_squared: F,
+    ) -> Option<(usize, f32)>
+    where
+        F: FnMut(usize) -> f32,
+    {
+        let mut best: Option<(usize, f32)> = None;
+        let mut best_d2 = max_distance_squared;
+        if self.nodes.is_empty() {
+            return best;
+        }
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if node.aabb.distance_squared(p) >= best_d2 {
+                continue;
+            }
+            if node.count > 0 {
+                for &prim in &self.primitives[node.start..node.start + node.count] {
+                    let d2 = distance_squared(prim);
+                    if d2 < best_d2 {
+                        best_d2 = d2;
+                        best = Some((prim, d2));
+                    }
+                }
+            } else {
+                //优先访问较近的子节点
+                let left = node_index + 1;
+                let right = node.start;
+                let dl = self.nodes[left].aabb.distance_squared(p);
+                let dr = self.nodes[right].aabb.distance_squared(p);
+                if dl < dr {
+                    stack.push(right);
+                    stack.push(left);
+                } else {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+        return best;
+    }
+}
@@ -1 +1,115 @@
-pub trait Shape {}
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+use super::intersect::{ContactInfo, GetClosestSurfacePoint};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Pose {
+    pub position: Point3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+}
+
+impl Pose {
+    pub fn identity() -> Pose {
+        return Pose {
+            position: Point3::origin(),
+            rotation: UnitQuaternion::identity(),
+        };
+    }
+
+    pub fn transform_point(&self, p: &Point3<f32>) -> Point3<f32> {
+        return self.position + self.rotation * p.coords;
+    }
+
+    pub fn inverse_transform_point(&self, p: &Point3<f32>) -> Point3<f32> {
+        return Point3::from(self.rotation.inverse_transform_vector(&(p - self.position)));
+    }
+}
+
+//形状定义在碰撞体的局部空间中，由pose变换到世界空间
+pub struct Collider<T> {
+    shape: T,
+    pose: Pose,
+    linear_velocity: Vector3<f32>,
+    angular_velocity: Vector3<f32>,
+    //距离上一次设置pose所经过的模拟时间
+    elapsed: f32,
+    //本帧开始时的pose及其到本帧的模拟时间，同一帧内多次设置pose时以此估算速度
+    start_pose: Pose,
+    start_elapsed: f32,
+    //加入后第一次设置pose视为瞬移，不产生速度
+    has_pose: bool,
+    //本次update之前是否设置过pose，没有设置时视为静止
+    is_pose_updated: bool,
+}
+
+impl<T> Collider<T> {
+    pub fn new(shape: T) -> Collider<T> {
+        return Collider {
+            shape: shape,
+            pose: Pose::identity(),
+            linear_velocity: Vector3::zeros(),
+            angular_velocity: Vector3::zeros(),
+            elapsed: 0.0,
+            start_pose: Pose::identity(),
+            start_elapsed: 0.0,
+            has_pose: false,
+            is_pose_updated: false,
+        };
+    }
+
+    pub fn into_shape(self) -> T {
+        return self.shape;
+    }
+
+    //根据两次设置之间经过的模拟时间估算碰撞体速度
+    pub fn set_pose(&mut self, pose: Pose) {
+        //两次设置之间没有经过模拟时间时属于同一帧，保留帧开始时的pose，只替换目标pose
+        if self.elapsed > 0.0 || !self.has_pose {
+            self.start_pose = self.pose;
+            self.start_elapsed = if self.has_pose { self.elapsed } else { 0.0 };
+        }
+        if self.start_elapsed > 0.0 {
+            self.linear_velocity = (pose.position - self.start_pose.position) / self.start_elapsed;
+            let d_rotation = pose.rotation * self.start_pose.rotation.inverse();
+            self.angular_velocity = d_rotation.scaled_axis() / self.start_elapsed;
+        } else {
+            self.linear_velocity = Vector3::zeros();
+            self.angular_velocity = Vector3::zeros();
+        }
+        self.pose = pose;
+        self.elapsed = 0.0;
+        self.has_pose = true;
+        self.is_pose_updated = true;
+    }
+
+    //每次update开始时调用，上一次update之后没有设置pose的碰撞体速度归零
+    pub fn begin_update(&mut self) {
+        if !self.is_pose_updated {
+            self.linear_velocity = Vector3::zeros();
+            self.angular_velocity = Vector3::zeros();
+        }
+        self.is_pose_updated = false;
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed += dt;
+    }
+
+    pub fn velocity_at(&self, p: &Point3<f32>) -> Vector3<f32> {
+        return self.linear_velocity + self.angular_velocity.cross(&(p - self.pose.position));
+    }
+}
+
+impl<T: GetClosestSurfacePoint> GetClosestSurfacePoint for Collider<T> {
+    fn get_closest_surface_point(&self, p: &Point3<f32>) -> ContactInfo {
+        let local_p = self.pose.inverse_transform_point(p);
+        let mut info = self.shape.get_closest_surface_point(&local_p);
+        if info.contacted {
+            info.contact_position = self.pose.transform_point(&info.contact_position);
+            info.contact_normal = self.pose.rotation * info.contact_normal;
+            info.contact_velocity = self.velocity_at(&info.contact_position);
+        }
+        return info;
+    }
+}
@@ -1,6 +1,6 @@
 use nalgebra::{Point3, SimdPartialOrd, SimdValue, Vector3};
 
-use super::shapes::{InfinitePlane, Sphere};
+use super::shapes::{Capsule, InfinitePlane, OrientedBox, Sphere};
 
 type Point = Point3<f32>;
 
@@ -10,12 +10,14 @@ pub struct ContactInfo {
     pub contacted: bool,
     pub contact_position: Point,
     pub contact_normal: Vector3<f32>,
+    pub contact_velocity: Vector3<f32>,
 }
 
-const CONTACTED_FALSE: ContactInfo = ContactInfo {
+pub(crate) const CONTACTED_FALSE: ContactInfo = ContactInfo {
     contacted: false,
     contact_position: point![0., 0., 0.],
     contact_normal: vector![0., 0., 0.],
+    contact_velocity: vector![0., 0., 0.],
 };
 
 pub trait GetClosestSurfacePoint {
@@ -35,6 +37,7 @@ impl GetClosestSurfacePoint for Sphere {
                 contacted: true,
                 contact_normal: normal,
                 contact_position: self.center + normal * self.radius,
+                contact_velocity: Vector3::zeros(),
             };
         } else {
             return CONTACTED_FALSE;
@@ -42,6 +45,55 @@ impl GetClosestSurfacePoint for Sphere {
     }
 }
 
+impl GetClosestSurfacePoint for Capsule {
+    fn get_closest_surface_point(&self, p: &Point) -> ContactInfo {
+        //投影到胶囊体中轴线段上，转化为球体求交
+        let axis = self.end - self.start;
+        let axis_len2 = axis.dot(&axis);
+        let t = if axis_len2 > 0.0 {
+            ((p - self.start).dot(&axis) / axis_len2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let sphere = Sphere {
+            center: self.start + axis * t,
+            radius: self.radius,
+        };
+        return sphere.get_closest_surface_point(p);
+    }
+}
+
+impl GetClosestSurfacePoint for OrientedBox {
+    fn get_closest_surface_point(&self, p: &Point) -> ContactInfo {
+        //转换到盒子的局部空间
+        let local = self.rotation.inverse_transform_vector(&(p - self.center));
+        let mut min_depth = f32::MAX;
+        let mut axis = 0;
+        for i in 0..3 {
+            let depth = self.half_extents[i] - local[i].abs();
+            if depth <= 0.0 {
+                return CONTACTED_FALSE;
+            }
+            if depth < min_depth {
+                min_depth = depth;
+                axis = i;
+            }
+        }
+        //从穿透深度最小的面推出
+        let sign = if local[axis] < 0.0 { -1.0 } else { 1.0 };
+        let mut local_normal = Vector3::<f32>::zeros();
+        local_normal[axis] = sign;
+        let mut local_contact = local;
+        local_contact[axis] = sign * self.half_extents[axis];
+        return ContactInfo {
+            contacted: true,
+            contact_normal: self.rotation * local_normal,
+            contact_position: self.center + self.rotation * local_contact,
+            contact_velocity: Vector3::zeros(),
+        };
+    }
+}
+
 impl GetClosestSurfacePoint for InfinitePlane {
     fn get_closest_surface_point(&self, p: &Point) -> ContactInfo {
         let pv: Vector3<f32> = vector![p.x, p.y, p.z];
@@ -53,6 +105,7 @@ impl GetClosestSurfacePoint for InfinitePlane {
                 contacted: true,
                 contact_normal: self.normal,
                 contact_position: p - self.normal * d,
+                contact_velocity: Vector3::zeros(),
             };
         }
     }
@@ -1,3 +1,7 @@
-mod collider;
+mod bvh;
+pub mod collider;
 pub(crate) mod intersect;
+pub mod sdf;
 pub mod shapes;
+pub mod spatial_hash;
+pub mod triangle_mesh;
@@ -0,0 +1,113 @@
+use nalgebra::{Point3, Vector3};
+
+use super::intersect::{ContactInfo, GetClosestSurfacePoint, CONTACTED_FALSE};
+
+const NORMALIZE_EPS: f32 = 0.0001;
+
+//体素有向距离场碰撞体，distances按x优先、z最后的顺序存放各格点的距离值
+pub struct SdfCollider {
+    origin: Point3<f32>,
+    cell_size: f32,
+    dims: [usize; 3],
+    distances: Vec<f32>,
+}
+
+impl SdfCollider {
+    pub fn new(
+        origin: Point3<f32>,
+        cell_size: f32,
+        dims: [usize; 3],
+        distances: Vec<f32>,
+    ) -> Result<SdfCollider, &'static str> {
+        if !cell_size.is_finite() || cell_size <= 0.0 {
+            return Err("cell_size must be positive");
+        }
+        if dims.iter().any(|d| *d < 2) {
+            return Err("sdf needs at least 2 samples on each axis");
+        }
+        let count = dims[0]
+            .checked_mul(dims[1])
+            .and_then(|c| c.checked_mul(dims[2]));
+        if count != Some(distances.len()) {
+            return Err("distances.len() != dims.x * dims.y * dims.z");
+        }
+        return Ok(SdfCollider {
+            origin: origin,
+            cell_size: cell_size,
+            dims: dims,
+            distances: distances,
+        });
+    }
+
+    fn value(&self, x: usize, y: usize, z: usize) -> f32 {
+        return self.distances[(z * self.dims[1] + y) * self.dims[0] + x];
+    }
+
+    //三线性插值采样，超出网格范围时返回None
+    fn sample(&self, p: &Point3<f32>) -> Option<f32> {
+        let local = (p - self.origin) / self.cell_size;
+        let mut cell = [0usize; 3];
+        let mut t = [0f32; 3];
+        for i in 0..3 {
+            let max = (self.dims[i] - 1) as f32;
+            if !(local[i] >= 0.0 && local[i] <= max) {
+                return None;
+            }
+            let c = local[i].floor().min(max - 1.0);
+            cell[i] = c as usize;
+            t[i] = local[i] - c;
+        }
+        let [x, y, z] = cell;
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        let c00 = lerp(self.value(x, y, z), self.value(x + 1, y, z), t[0]);
+        let c10 = lerp(self.value(x, y + 1, z), self.value(x + 1, y + 1, z), t[0]);
+        let c01 = lerp(self.value(x, y, z + 1), self.value(x + 1, y, z + 1), t[0]);
+        let c11 = lerp(
+            self.value(x, y + 1, z + 1),
+            self.value(x + 1, y + 1, z + 1),
+            t[0],
+        );
+        let c0 = lerp(c00, c10, t[1]);
+        let c1 = lerp(c01, c11, t[1]);
+        return Some(lerp(c0, c1, t[2]));
+    }
+
+    //中心差分梯度，越界一侧退化为单侧差分
+    fn gradient(&self, p: &Point3<f32>, d: f32) -> Vector3<f32> {
+        let h = self.cell_size * 0.5;
+        let mut grad = Vector3::zeros();
+        for i in 0..3 {
+            let mut offset = Vector3::zeros();
+            offset[i] = h;
+            let forward = self.sample(&(p + offset));
+            let backward = self.sample(&(p - offset));
+            grad[i] = match (forward, backward) {
+                (Some(f), Some(b)) => (f - b) / (2.0 * h),
+                (Some(f), None) => (f - d) / h,
+                (None, Some(b)) => (d - b) / h,
+                (None, None) => 0.0,
+            };
+        }
+        return grad;
+    }
+}
+
+impl GetClosestSurfacePoint for SdfCollider {
+    fn get_closest_surface_point(&self, p: &Point3<f32>) -> ContactInfo {
+        match self.sample(p) {
+            Some(d) if d < 0.0 => {
+                let normal = match self.gradient(p, d).try_normalize(NORMALIZE_EPS) {
+                    Some(n) => n,
+                    None => return CONTACTED_FALSE,
+                };
+                return ContactInfo {
+                    contacted: true,
+                    contact_position: p - normal * d,
+                    contact_normal: normal,
+                    contact_velocity: Vector3::zeros(),
+                };
+            }
+            _ => CONTACTED_FALSE,
+        }
+    }
+}
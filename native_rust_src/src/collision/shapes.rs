@@ -1,4 +1,11 @@
-use nalgebra::{Point3, Vector3};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+use super::{
+    collider::{Collider, Pose},
+    intersect::{ContactInfo, GetClosestSurfacePoint},
+    sdf::SdfCollider,
+    triangle_mesh::TriangleMeshCollider,
+};
 
 #[repr(C)]
 pub struct Sphere {
@@ -6,54 +13,274 @@ pub struct Sphere {
     pub radius: f32,
 }
 
+#[repr(C)]
+pub struct Capsule {
+    pub start: Point3<f32>,
+    pub end: Point3<f32>,
+    pub radius: f32,
+}
+
+#[repr(C)]
+pub struct OrientedBox {
+    pub center: Point3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    pub half_extents: Vector3<f32>,
+}
+
 #[repr(C)]
 pub struct InfinitePlane {
     pub normal: Vector3<f32>,
     pub origin_to_plane: f32,
 }
 
+//移除碰撞体时不移动其它元素，已分配的索引在整个生命周期内保持稳定
 pub struct ShapeSet<T> {
-    shapes: Vec<T>,
+    colliders: Vec<Option<Collider<T>>>,
+    free_slots: Vec<usize>,
 }
 
 impl<T> ShapeSet<T> {
     pub fn add(&mut self, shape: T) -> usize {
-        let index = self.shapes.len();
-        self.shapes.push(shape);
-        return index;
+        let collider = Some(Collider::new(shape));
+        match self.free_slots.pop() {
+            Some(index) => {
+                self.colliders[index] = collider;
+                return index;
+            }
+            None => {
+                self.colliders.push(collider);
+                return self.colliders.len() - 1;
+            }
+        }
     }
+
     pub fn remove(&mut self, index: usize) -> Option<T> {
-        if index < self.shapes.len() {
-            Some(self.shapes.swap_remove(index))
-        } else {
-            None
+        let collider = self.colliders.get_mut(index)?.take()?;
+        self.free_slots.push(index);
+        return Some(collider.into_shape());
+    }
+
+    //下一次add将使用的索引
+    fn next_index(&self) -> usize {
+        return match self.free_slots.last() {
+            Some(index) => *index,
+            None => self.colliders.len(),
+        };
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Collider<T>> {
+        return self.colliders.get_mut(index)?.as_mut();
+    }
+
+    pub fn colliders(&self) -> impl Iterator<Item = &Collider<T>> {
+        return self.colliders.iter().filter_map(|c| c.as_ref());
+    }
+
+    pub fn begin_update(&mut self) {
+        for c in self.colliders.iter_mut().filter_map(|c| c.as_mut()) {
+            c.begin_update();
         }
     }
 
-    pub fn shapes(&self) -> &[T] {
-        return &self.shapes;
+    pub fn advance(&mut self, dt: f32) {
+        for c in self.colliders.iter_mut().filter_map(|c| c.as_mut()) {
+            c.advance(dt);
+        }
     }
 
     pub fn clear(&mut self) {
-        self.shapes.clear();
+        self.colliders.clear();
+        self.free_slots.clear();
     }
 }
 
 impl<T> Default for ShapeSet<T> {
     fn default() -> Self {
-        Self { shapes: vec![] }
+        Self {
+            colliders: vec![],
+            free_slots: vec![],
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShapeKind {
+    Sphere = 0,
+    Capsule = 1,
+    Box = 2,
+    InfinitePlane = 3,
+    TriangleMesh = 4,
+    Sdf = 5,
+}
+
+//碰撞体句柄: 高8位为形状类型，低24位为ShapeSet中的索引
+pub type ColliderHandle = u32;
+
+const HANDLE_INDEX_BITS: u32 = 24;
+const HANDLE_INDEX_MASK: u32 = (1 << HANDLE_INDEX_BITS) - 1;
+//形状类型无效，split_collider_handle总是返回None
+pub const INVALID_COLLIDER_HANDLE: ColliderHandle = u32::MAX;
+
+//索引超出24位时返回None
+pub fn make_collider_handle(kind: ShapeKind, index: usize) -> Option<ColliderHandle> {
+    if index > HANDLE_INDEX_MASK as usize {
+        return None;
     }
+    return Some(((kind as u32) << HANDLE_INDEX_BITS) | index as u32);
+}
+
+//同一类型的碰撞体超过句柄能表示的数量时不加入，返回INVALID_COLLIDER_HANDLE
+fn add_to_set<T>(set: &mut ShapeSet<T>, kind: ShapeKind, shape: T) -> ColliderHandle {
+    if make_collider_handle(kind, set.next_index()).is_none() {
+        return INVALID_COLLIDER_HANDLE;
+    }
+    return make_collider_handle(kind, set.add(shape)).unwrap();
+}
+
+pub fn split_collider_handle(handle: ColliderHandle) -> Option<(ShapeKind, usize)> {
+    let kind = match handle >> HANDLE_INDEX_BITS {
+        0 => ShapeKind::Sphere,
+        1 => ShapeKind::Capsule,
+        2 => ShapeKind::Box,
+        3 => ShapeKind::InfinitePlane,
+        4 => ShapeKind::TriangleMesh,
+        5 => ShapeKind::Sdf,
+        _ => return None,
+    };
+    return Some((kind, (handle & HANDLE_INDEX_MASK) as usize));
 }
 
 #[derive(Default)]
 pub struct ShapesManager {
     pub spheres: ShapeSet<Sphere>,
+    pub capsules: ShapeSet<Capsule>,
+    pub boxes: ShapeSet<OrientedBox>,
     pub infinite_planes: ShapeSet<InfinitePlane>,
+    pub triangle_meshes: ShapeSet<TriangleMeshCollider>,
+    pub sdfs: ShapeSet<SdfCollider>,
 }
 
 impl ShapesManager {
+    pub fn add_sphere(&mut self, shape: Sphere) -> ColliderHandle {
+        return add_to_set(&mut self.spheres, ShapeKind::Sphere, shape);
+    }
+
+    pub fn add_capsule(&mut self, shape: Capsule) -> ColliderHandle {
+        return add_to_set(&mut self.capsules, ShapeKind::Capsule, shape);
+    }
+
+    pub fn add_box(&mut self, shape: OrientedBox) -> ColliderHandle {
+        return add_to_set(&mut self.boxes, ShapeKind::Box, shape);
+    }
+
+    pub fn add_infinite_plane(&mut self, shape: InfinitePlane) -> ColliderHandle {
+        return add_to_set(&mut self.infinite_planes, ShapeKind::InfinitePlane, shape);
+    }
+
+    pub fn add_triangle_mesh(&mut self, shape: TriangleMeshCollider) -> ColliderHandle {
+        return add_to_set(&mut self.triangle_meshes, ShapeKind::TriangleMesh, shape);
+    }
+
+    pub fn add_sdf(&mut self, shape: SdfCollider) -> ColliderHandle {
+        return add_to_set(&mut self.sdfs, ShapeKind::Sdf, shape);
+    }
+
+    pub fn remove(&mut self, handle: ColliderHandle) -> bool {
+        return match split_collider_handle(handle) {
+            Some((ShapeKind::Sphere, index)) => self.spheres.remove(index).is_some(),
+            Some((ShapeKind::Capsule, index)) => self.capsules.remove(index).is_some(),
+            Some((ShapeKind::Box, index)) => self.boxes.remove(index).is_some(),
+            Some((ShapeKind::InfinitePlane, index)) => self.infinite_planes.remove(index).is_some(),
+            Some((ShapeKind::TriangleMesh, index)) => self.triangle_meshes.remove(index).is_some(),
+            Some((ShapeKind::Sdf, index)) => self.sdfs.remove(index).is_some(),
+            None => false,
+        };
+    }
+
+    //只移除kind类型的碰撞体，句柄属于其它类型时返回false
+    pub fn remove_of_kind(&mut self, kind: ShapeKind, handle: ColliderHandle) -> bool {
+        return match split_collider_handle(handle) {
+            Some((handle_kind, _)) if handle_kind == kind => self.remove(handle),
+            _ => false,
+        };
+    }
+
+    pub fn set_pose(&mut self, handle: ColliderHandle, pose: Pose) -> bool {
+        let result = match split_collider_handle(handle) {
+            Some((ShapeKind::Sphere, index)) => {
+                self.spheres.get_mut(index).map(|c| c.set_pose(pose))
+            }
+            Some((ShapeKind::Capsule, index)) => {
+                self.capsules.get_mut(index).map(|c| c.set_pose(pose))
+            }
+            Some((ShapeKind::Box, index)) => self.boxes.get_mut(index).map(|c| c.set_pose(pose)),
+            Some((ShapeKind::InfinitePlane, index)) => self
+                .infinite_planes
+                .get_mut(index)
+                .map(|c| c.set_pose(pose)),
+            Some((ShapeKind::TriangleMesh, index)) => self
+                .triangle_meshes
+                .get_mut(index)
+                .map(|c| c.set_pose(pose)),
+            Some((ShapeKind::Sdf, index)) => self.sdfs.get_mut(index).map(|c| c.set_pose(pose)),
+            None => None,
+        };
+        return result.is_some();
+    }
+
+    //点p位于碰撞体内部时以接触信息调用f
+    pub fn for_each_contact<F: FnMut(&ContactInfo)>(&self, p: &Point3<f32>, mut f: F) {
+        let mut visit = |info: ContactInfo| {
+            if info.contacted {
+                f(&info);
+            }
+        };
+        for c in self.spheres.colliders() {
+            visit(c.get_closest_surface_point(p));
+        }
+        for c in self.capsules.colliders() {
+            visit(c.get_closest_surface_point(p));
+        }
+        for c in self.boxes.colliders() {
+            visit(c.get_closest_surface_point(p));
+        }
+        for c in self.infinite_planes.colliders() {
+            visit(c.get_closest_surface_point(p));
+        }
+        for c in self.triangle_meshes.colliders() {
+            visit(c.get_closest_surface_point(p));
+        }
+        for c in self.sdfs.colliders() {
+            visit(c.get_closest_surface_point(p));
+        }
+    }
+
+    pub fn begin_update(&mut self) {
+        self.spheres.begin_update();
+        self.capsules.begin_update();
+        self.boxes.begin_update();
+        self.infinite_planes.begin_update();
+        self.triangle_meshes.begin_update();
+        self.sdfs.begin_update();
+    }
+
+    //推进模拟时间，用于估算运动碰撞体的速度
+    pub fn advance(&mut self, dt: f32) {
+        self.spheres.advance(dt);
+        self.capsules.advance(dt);
+        self.boxes.advance(dt);
+        self.infinite_planes.advance(dt);
+        self.triangle_meshes.advance(dt);
+        self.sdfs.advance(dt);
+    }
+
     pub fn clear(&mut self) {
         self.spheres.clear();
+        self.capsules.clear();
+        self.boxes.clear();
         self.infinite_planes.clear();
+        self.triangle_meshes.clear();
+        self.sdfs.clear();
     }
 }
@@ -0,0 +1,87 @@
+use nalgebra::Point3;
+
+//稠密空间哈希表，每次查询前整体重建
+pub struct SpatialHash {
+    cell_size: f32,
+    cell_starts: Vec<usize>,
+    cell_entries: Vec<usize>,
+}
+
+impl SpatialHash {
+    pub fn new() -> SpatialHash {
+        return SpatialHash {
+            cell_size: 1.0,
+            cell_starts: vec![],
+            cell_entries: vec![],
+        };
+    }
+
+    fn cell_coord(&self, p: &Point3<f32>) -> [i32; 3] {
+        return [
+            (p.x / self.cell_size).floor() as i32,
+            (p.y / self.cell_size).floor() as i32,
+            (p.z / self.cell_size).floor() as i32,
+        ];
+    }
+
+    fn hash(&self, coord: [i32; 3]) -> usize {
+        let h = (coord[0].wrapping_mul(92837111))
+            ^ (coord[1].wrapping_mul(689287499))
+            ^ (coord[2].wrapping_mul(283923481));
+        return h.unsigned_abs() as usize % (self.cell_starts.len() - 1);
+    }
+
+    pub fn build<'a, I>(&mut self, positions: I, count: usize, cell_size: f32)
+    where
+        I: Iterator<Item = &'a Point3<f32>> + Clone,
+    {
+        self.cell_size = cell_size.max(f32::EPSILON);
+        let table_size = 2 * count.max(1);
+        self.cell_starts.clear();
+        self.cell_starts.resize(table_size + 1, 0);
+        self.cell_entries.clear();
+        self.cell_entries.resize(count, 0);
+
+        //计数排序：先统计每个格子的元素数，再转为前缀和
+        for p in positions.clone() {
+            let h = self.hash(self.cell_coord(p));
+            self.cell_starts[h] += 1;
+        }
+        let mut start = 0;
+        for c in self.cell_starts.iter_mut() {
+            start += *c;
+            *c = start;
+        }
+        for (index, p) in positions.enumerate() {
+            let h = self.hash(self.cell_coord(p));
+            self.cell_starts[h] -= 1;
+            self.cell_entries[self.cell_starts[h]] = index;
+        }
+    }
+
+    //遍历p所在格子及其相邻26个格子中的元素，哈希冲突可能带来额外的元素
+    pub fn query<F>(&self, p: &Point3<f32>, mut f: F)
+    where
+        F: FnMut(usize),
+    {
+        if self.cell_entries.is_empty() {
+            return;
+        }
+        let c = self.cell_coord(p);
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    //格子坐标在转换时饱和到i32范围，相邻格子需要回绕而不是溢出
+                    let h = self.hash([
+                        c[0].wrapping_add(x),
+                        c[1].wrapping_add(y),
+                        c[2].wrapping_add(z),
+                    ]);
+                    for &index in &self.cell_entries[self.cell_starts[h]..self.cell_starts[h + 1]] {
+                        f(index);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use nalgebra::{Point3, Vector3};
+
+use super::{
+    bvh::{Aabb, Bvh},
+    intersect::{ContactInfo, GetClosestSurfacePoint, CONTACTED_FALSE},
+};
+
+//三角网格碰撞体，三角形以逆时针为正面，质点位于某个三角形背面且深度小于thickness时视为接触
+pub struct TriangleMeshCollider {
+    vertices: Vec<Point3<f32>>,
+    triangles: Vec<[usize; 3]>,
+    normals: Vec<Vector3<f32>>,
+    thickness: f32,
+    bvh: Bvh,
+}
+
+impl TriangleMeshCollider {
+    pub fn new(
+        vertices: Vec<Point3<f32>>,
+        triangles: Vec<[usize; 3]>,
+        thickness: f32,
+    ) -> Result<TriangleMeshCollider, &'static str> {
+        if triangles.iter().flatten().any(|i| *i >= vertices.len()) {
+            return Err("triangle index out of range");
+        }
+        //面积为0的三角形没有法线，无法判断正反面，建树时直接丢弃
+        let mut kept = Vec::with_capacity(triangles.len());
+        let mut aabbs = Vec::with_capacity(triangles.len());
+        let mut normals = Vec::with_capacity(triangles.len());
+        for t in triangles {
+            let (a, b, c) = (&vertices[t[0]], &vertices[t[1]], &vertices[t[2]]);
+            let normal = match (b - a).cross(&(c - a)).try_normalize(f32::EPSILON) {
+                Some(normal) => normal,
+                None => continue,
+            };
+            let mut aabb = Aabb::empty();
+            aabb.grow(a);
+            aabb.grow(b);
+            aabb.grow(c);
+            aabbs.push(aabb);
+            normals.push(normal);
+            kept.push(t);
+        }
+        return Ok(TriangleMeshCollider {
+            bvh: Bvh::build(&aabbs),
+            vertices: vertices,
+            triangles: kept,
+            normals: normals,
+            thickness: thickness,
+        });
+    }
+
+    fn closest_point_on_triangle(&self, triangle: usize, p: &Point3<f32>) -> Point3<f32> {
+        let t = &self.triangles[triangle];
+        return closest_point_on_triangle(
+            p,
+            &self.vertices[t[0]],
+            &self.vertices[t[1]],
+            &self.vertices[t[2]],
+        );
+    }
+}
+
+//Real-Time Collision Detection 5.1.5
+fn closest_point_on_triangle(
+    p: &Point3<f32>,
+    a: &Point3<f32>,
+    b: &Point3<f32>,
+    c: &Point3<f32>,
+) -> Point3<f32> {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+    let bp = p - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denom = 1.0 / (va + vb + vc);
+    return a + ab * (vb * denom) + ac * (vc * denom);
+}
+
+impl GetClosestSurfacePoint for TriangleMeshCollider {
+    fn get_closest_surface_point(&self, p: &Point3<f32>) -> ContactInfo {
+        let nearest = self
+            .bvh
+            .find_nearest(p, self.thickness * self.thickness, |triangle| {
+                (self.closest_point_on_triangle(triangle, p) - p).norm_squared()
+            });
+        match nearest {
+            Some((triangle, _)) => {
+                let closest = self.closest_point_on_triangle(triangle, p);
+                let normal = self.normals[triangle];
+                if (p - closest).dot(&normal) > 0.0 {
+                    return CONTACTED_FALSE;
+                }
+                return ContactInfo {
+                    contacted: true,
+                    contact_position: closest,
+                    contact_normal: normal,
+                    contact_velocity: Vector3::zeros(),
+                };
+            }
+            None => CONTACTED_FALSE,
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use nalgebra::{distance, Point3, Vector3};
 
-use crate::constraints::{constraint::Constraint, particle::ParticleData};
+use crate::constraints::{
+    constraint::{Constraint, LambdaBound},
+    particle::ParticleData,
+};
 
 pub struct CollisionConstraint {
     pub concat_position: Point3<f32>,
     pub concat_normal: Vector3<f32>,
+    //碰撞体表面在接触点处的速度
+    pub concat_velocity: Vector3<f32>,
     p_index: [usize; 1],
 }
 
@@ -13,11 +18,13 @@ impl CollisionConstraint {
         p_index: usize,
         concat_position: Point3<f32>,
         normal: Vector3<f32>,
+        velocity: Vector3<f32>,
     ) -> CollisionConstraint {
         return CollisionConstraint {
             p_index: [p_index],
             concat_position: concat_position,
             concat_normal: normal,
+            concat_velocity: velocity,
         };
     }
 }
@@ -29,27 +36,74 @@ impl Constraint for CollisionConstraint {
     fn get_stiffness_inv(&self) -> f32 {
         return 0.;
     }
+    //穿透深度，C <= 0
     fn get_value(&self, particles_data: &[ParticleData]) -> f32 {
         let pos = &particles_data[self.p_index[0]].predict_position;
-        return (self.concat_position - pos).dot(&self.concat_normal).max(0.0)
+        return (self.concat_position - pos).dot(&self.concat_normal);
     }
 
-    fn get_grad(&self, particles_data: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
-        let pos = &particles_data[self.p_index[0]].predict_position;
-        let target_pos = &self.concat_position;
-        let norm_result = (pos - target_pos).try_normalize(EPS);
-        match norm_result {
-            Some(grad) => {
-                out_grads[0] = grad;
-            }
-            None => out_grads[0] = Vector3::identity(),
-        }
+    fn get_grad(&self, _: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        out_grads[0] = -self.concat_normal;
+    }
+    //只能把质点推出碰撞体表面
+    fn get_lambda_bound(&self, _: &[ParticleData]) -> LambdaBound {
+        return LambdaBound::NonPositive;
     }
     fn get_pindexes(&self) -> &[usize] {
         return &self.p_index;
     }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_index;
+    }
 
-    fn get_constraint_particle_count() -> usize {
+    fn get_constraint_particle_count(&self) -> usize {
         return 1;
     }
 }
+
+//质点间的自碰撞约束，两质点距离不小于半径之和
+pub struct ParticleCollisionConstraint {
+    p_indexes: [usize; 2],
+    min_distance: f32,
+}
+
+impl ParticleCollisionConstraint {
+    pub fn new(p1: usize, p2: usize, min_distance: f32) -> ParticleCollisionConstraint {
+        return ParticleCollisionConstraint {
+            p_indexes: [p1, p2],
+            min_distance: min_distance,
+        };
+    }
+}
+
+impl Constraint for ParticleCollisionConstraint {
+    fn on_initialize(&mut self, _: &[ParticleData]) {}
+    fn get_stiffness_inv(&self) -> f32 {
+        return 0.;
+    }
+    fn get_value(&self, particles_data: &[ParticleData]) -> f32 {
+        let p1 = &particles_data[self.p_indexes[0]].predict_position;
+        let p2 = &particles_data[self.p_indexes[1]].predict_position;
+        return distance(p1, p2) - self.min_distance;
+    }
+    fn get_grad(&self, particles_data: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        let p1 = &particles_data[self.p_indexes[0]].predict_position;
+        let p2 = &particles_data[self.p_indexes[1]].predict_position;
+        let n = (p1 - p2).try_normalize(EPS).unwrap_or(vector![0., 1., 0.]);
+        out_grads[0] = n;
+        out_grads[1] = -n;
+    }
+    //只能把两质点推开，C >= 0
+    fn get_lambda_bound(&self, _: &[ParticleData]) -> LambdaBound {
+        return LambdaBound::NonNegative;
+    }
+    fn get_pindexes(&self) -> &[usize] {
+        return &self.p_indexes;
+    }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
+        return 2;
+    }
+}
@@ -1,3 +1,5 @@
+use std::{collections::HashMap, ops::Range};
+
 use nalgebra::Vector3;
 
 use super::particle::ParticleData;
@@ -5,30 +7,147 @@ use super::particle::ParticleData;
 pub trait Constraint {
     fn on_initialize(&mut self, particles_data: &[ParticleData]);
     fn get_stiffness_inv(&self) -> f32;
-    fn get_constraint_particle_count() -> usize;
+    //每个实例可以有不同的质点数量，决定其梯度在ConstraintsData::grads中占用的长度
+    fn get_constraint_particle_count(&self) -> usize;
     fn get_pindexes(&self) -> &[usize];
     fn get_value(&self, particles_data: &[ParticleData]) -> f32;
     fn get_grad(&self, particles_data: &[ParticleData], out_grads: &mut [Vector3<f32>]);
+    //质点复制(撕裂)时用于重新指向新质点
+    fn get_pindexes_mut(&mut self) -> &mut [usize];
+    //不等式约束通过限制累计的lambda实现，默认为等式约束。
+    //可以随质点位置变化，例如距离范围约束在两端分别使用不同的限制
+    fn get_lambda_bound(&self, _: &[ParticleData]) -> LambdaBound {
+        return LambdaBound::Free;
+    }
+    //XPBD论文中的阻尼系数beta，默认不阻尼
+    fn get_damping(&self) -> f32 {
+        return 0.0;
+    }
+    //默认不可断裂
+    fn get_break_threshold(&self) -> BreakThreshold {
+        return BreakThreshold::NONE;
+    }
+    //按应变判断断裂时使用的值，默认为约束值的绝对值
+    fn get_strain(&self, particles_data: &[ParticleData]) -> f32 {
+        return self.get_value(particles_data).abs();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LambdaBound {
+    //等式约束 C = 0
+    Free,
+    //lambda >= 0，对应 C >= 0
+    NonNegative,
+    //lambda <= 0，对应 C <= 0
+    NonPositive,
+}
+
+impl LambdaBound {
+    pub fn clamp(&self, lambda: f32) -> f32 {
+        return match self {
+            LambdaBound::Free => lambda,
+            LambdaBound::NonNegative => lambda.max(0.0),
+            LambdaBound::NonPositive => lambda.min(0.0),
+        };
+    }
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakKind {
+    None = 0,
+    //约束的应变超过阈值时断裂
+    Strain = 1,
+    //约束力 |lambda| / dt^2 超过阈值时断裂
+    Force = 2,
+}
+
+impl BreakKind {
+    //C#传入的值不在枚举范围内时返回None
+    pub fn from_u32(value: u32) -> Option<BreakKind> {
+        return match value {
+            0 => Some(BreakKind::None),
+            1 => Some(BreakKind::Strain),
+            2 => Some(BreakKind::Force),
+            _ => None,
+        };
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct BreakThreshold {
+    //BreakKind的原始值，由C#传入，通过kind()校验后读取
+    kind: u32,
+    pub value: f32,
+}
+
+impl BreakThreshold {
+    pub const NONE: BreakThreshold = BreakThreshold {
+        kind: BreakKind::None as u32,
+        value: 0.0,
+    };
+
+    pub fn new(kind: BreakKind, value: f32) -> BreakThreshold {
+        return BreakThreshold {
+            kind: kind as u32,
+            value: value,
+        };
+    }
+
+    //kind不合法时返回None
+    pub fn kind(&self) -> Option<BreakKind> {
+        return BreakKind::from_u32(self.kind);
+    }
+
+    pub fn is_valid(&self) -> bool {
+        return self.kind().is_some();
+    }
+
+    pub fn is_exceeded(&self, strain: f32, force: f32) -> bool {
+        return match self.kind() {
+            Some(BreakKind::Strain) => strain > self.value,
+            Some(BreakKind::Force) => force > self.value,
+            _ => false,
+        };
+    }
+}
+
+//约束的稳定标识，不随其它约束的移除而改变
+pub type ConstraintId = u32;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConstraintForce {
+    pub id: ConstraintId,
+    //lambda / dt^2，符号与约束梯度方向一致，拉伸的距离约束为负值
+    pub force: f32,
 }
 
 #[derive(Clone)]
 pub struct ConstraintData {
+    pub id: ConstraintId,
     //过程量，每次迭代都要重新计算
     pub lambda: f32,
     pub d_lambda: f32,
-    pub grads: Vec<Vector3<f32>>,
+    //梯度在ConstraintsData::grads中的起始位置
+    pub grad_offset: usize,
 
     //缓存量，一开始就能为每个约束计算好
     pub alpha: f32, //stifiness_inv / (dt * dt)
+    pub gamma: f32, //stiffness_inv * damping / dt
     pub is_cache_dirty: bool,
 }
 impl Default for ConstraintData {
     fn default() -> Self {
         Self {
+            id: Default::default(),
             lambda: Default::default(),
             d_lambda: Default::default(),
-            grads: Default::default(),
+            grad_offset: Default::default(),
             alpha: Default::default(),
+            gamma: Default::default(),
             is_cache_dirty: true,
         }
     }
@@ -37,6 +156,12 @@ impl Default for ConstraintData {
 pub struct ConstraintsData<T: Constraint> {
     pub defines: Vec<T>,
     pub datas: Vec<ConstraintData>,
+    //所有约束的梯度连续存放，避免为每个约束单独分配
+    pub grads: Vec<Vector3<f32>>,
+    is_grads_layout_dirty: bool,
+    next_id: ConstraintId,
+    //id -> 在defines中的下标，随push与swap_remove更新
+    id_to_index: HashMap<ConstraintId, usize>,
 }
 
 impl<T: Constraint> std::default::Default for ConstraintsData<T> {
@@ -44,16 +169,31 @@ impl<T: Constraint> std::default::Default for ConstraintsData<T> {
         Self {
             defines: vec![],
             datas: vec![],
+            grads: vec![],
+            is_grads_layout_dirty: true,
+            next_id: 0,
+            id_to_index: HashMap::new(),
         }
     }
 }
 
 impl<T: Constraint> From<Vec<T>> for ConstraintsData<T> {
     fn from(vec: Vec<T>) -> Self {
+        //初始约束的id即为其在vec中的下标
         let count = vec.len();
+        let mut datas = vec![ConstraintData::default(); count];
+        for (index, data) in datas.iter_mut().enumerate() {
+            data.id = index as ConstraintId;
+        }
         return ConstraintsData {
             defines: vec,
-            datas: vec![ConstraintData::default(); count],
+            datas: datas,
+            grads: vec![],
+            is_grads_layout_dirty: true,
+            next_id: count as ConstraintId,
+            id_to_index: (0..count)
+                .map(|index| (index as ConstraintId, index))
+                .collect(),
         };
     }
 }
@@ -62,21 +202,77 @@ impl<T: Constraint> ConstraintsData<T> {
     pub fn clear(&mut self) {
         self.defines.clear();
         self.datas.clear();
+        self.grads.clear();
+        self.id_to_index.clear();
+        self.is_grads_layout_dirty = true;
     }
 
-    pub fn push(&mut self, define: T) {
+    pub fn push(&mut self, define: T) -> ConstraintId {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.id_to_index.insert(id, self.defines.len());
         self.defines.push(define);
-        self.datas.push(ConstraintData::default());
+        self.datas.push(ConstraintData {
+            id: id,
+            ..Default::default()
+        });
+        self.is_grads_layout_dirty = true;
+        return id;
     }
 
     pub fn swap_remove(&mut self, index: usize) -> (T, ConstraintData) {
         let define = self.defines.swap_remove(index);
         let data = self.datas.swap_remove(index);
+        self.id_to_index.remove(&data.id);
+        //原来的最后一个约束被移到index
+        if let Some(moved) = self.datas.get(index) {
+            self.id_to_index.insert(moved.id, index);
+        }
+        self.is_grads_layout_dirty = true;
         return (define, data);
     }
 
+    pub fn index_of(&self, id: ConstraintId) -> Option<usize> {
+        return self.id_to_index.get(&id).copied();
+    }
+
+    pub fn remove_by_id(&mut self, id: ConstraintId) -> Option<T> {
+        let index = self.index_of(id)?;
+        return Some(self.swap_remove(index).0);
+    }
+
+    //时间步长变化后需要重新计算alpha
+    pub fn mark_cache_dirty(&mut self) {
+        for data in self.datas.iter_mut() {
+            data.is_cache_dirty = true;
+        }
+    }
+
+    //修改单个约束的刚度或阻尼后调用
+    pub fn mark_cache_dirty_at(&mut self, index: usize) {
+        self.datas[index].is_cache_dirty = true;
+    }
+
+    //第index个约束的梯度在grads中的范围
+    pub fn grad_range(&self, index: usize) -> Range<usize> {
+        let offset = self.datas[index].grad_offset;
+        return offset..offset + self.defines[index].get_constraint_particle_count();
+    }
+
+    fn update_grads_layout(&mut self) {
+        let mut offset = 0;
+        for (cs, data) in self.defines.iter().zip(self.datas.iter_mut()) {
+            data.grad_offset = offset;
+            offset += cs.get_constraint_particle_count();
+        }
+        self.grads.resize(offset, Vector3::zeros());
+        self.is_grads_layout_dirty = false;
+    }
+
     pub fn calculate_cache(&mut self, particles_data: &[ParticleData], dt: f32) {
-        let grad_dim = T::get_constraint_particle_count();
+        if self.is_grads_layout_dirty {
+            self.update_grads_layout();
+        }
         for (index, data) in self.datas.iter_mut().enumerate() {
             if !data.is_cache_dirty {
                 continue;
@@ -84,7 +280,7 @@ impl<T: Constraint> ConstraintsData<T> {
             let cs = &mut self.defines[index];
             data.is_cache_dirty = false;
             data.alpha = cs.get_stiffness_inv() / (dt * dt);
-            data.grads = vec![Vector3::<f32>::zeros(); grad_dim];
+            data.gamma = cs.get_stiffness_inv() * cs.get_damping() / dt;
         }
     }
 }
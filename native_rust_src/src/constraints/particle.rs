@@ -15,12 +15,23 @@ pub struct ParticleData {
     pub predict_position: Point3<f32>,
     pub d_position: Vector3<f32>,
     pub w: f32, // 1 / mass
+    pub radius: f32,
     pub flag: u8,
+    //单独施加在该质点上的力与加速度，每次update结束后清零
+    pub external_force: Vector3<f32>,
+    pub external_acceleration: Vector3<f32>,
+    //单个质点的线性阻尼，与全局阻尼叠加
+    pub damping: f32,
 }
 
 pub struct ParticlesDataBuilder {
     pub positions: Vec<Point3<f32>>,
     pub masses: Vec<f32>,
+    //为空时所有质点使用default_radius
+    pub radii: Vec<f32>,
+    pub default_radius: f32,
+    //为空时所有质点的damping为0
+    pub dampings: Vec<f32>,
 }
 
 impl ParticlesDataBuilder {
@@ -28,6 +39,12 @@ impl ParticlesDataBuilder {
         if self.positions.len() != self.masses.len() {
             return Err("positions.len() != masses.len()");
         }
+        if !self.radii.is_empty() && self.positions.len() != self.radii.len() {
+            return Err("positions.len() != radii.len()");
+        }
+        if !self.dampings.is_empty() && self.positions.len() != self.dampings.len() {
+            return Err("positions.len() != dampings.len()");
+        }
         let mut particles_data = Vec::<ParticleData>::with_capacity(self.positions.len());
         for (index, p) in self.positions.iter().enumerate() {
             particles_data.push(ParticleData {
@@ -36,13 +53,53 @@ impl ParticlesDataBuilder {
                 w: 1.0 / self.masses[index],
                 velocity: Default::default(),
                 d_position: Default::default(),
+                radius: *self.radii.get(index).unwrap_or(&self.default_radius),
                 flag: 0,
+                external_force: Vector3::zeros(),
+                external_acceleration: Vector3::zeros(),
+                damping: *self.dampings.get(index).unwrap_or(&0.0),
             });
         }
         return Ok(particles_data);
     }
 }
 
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleForceKind {
+    Force = 0,
+    Acceleration = 1,
+    //冲量直接改变速度 dv = impulse * w
+    Impulse = 2,
+}
+
+impl ParticleForceKind {
+    //C#传入的值不在枚举范围内时返回None
+    pub fn from_u32(value: u32) -> Option<ParticleForceKind> {
+        return match value {
+            0 => Some(ParticleForceKind::Force),
+            1 => Some(ParticleForceKind::Acceleration),
+            2 => Some(ParticleForceKind::Impulse),
+            _ => None,
+        };
+    }
+}
+
+impl ParticleData {
+    pub fn apply(&mut self, kind: ParticleForceKind, value: &Vector3<f32>) {
+        match kind {
+            ParticleForceKind::Force => self.external_force += value,
+            ParticleForceKind::Acceleration => self.external_acceleration += value,
+            ParticleForceKind::Impulse => self.velocity += value * self.w,
+        }
+    }
+
+    pub fn clear_external_forces(&mut self) {
+        self.external_force = Vector3::zeros();
+        self.external_acceleration = Vector3::zeros();
+    }
+}
+
 pub trait ParticlesCleaner {
     fn clear_d_positions(&mut self);
 }
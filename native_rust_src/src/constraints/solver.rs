@@ -1,44 +1,108 @@
-use std::f32::NAN;
+use std::{collections::HashSet, f32::NAN};
+
+use nalgebra::Vector3;
 
 use crate::{
     constraints::particle::ParticlesCleaner,
     debug::{ConstraintsDebug, ParticlesDebug},
+    tearing::{is_on_split_side, BreakEvent, NO_PARTICLE},
 };
 
 use super::{
-    constraint::{Constraint, ConstraintData, ConstraintsData},
+    constraint::{
+        BreakKind, Constraint, ConstraintData, ConstraintForce, ConstraintsData, LambdaBound,
+    },
     particle::ParticleData,
 };
 
+//带阻尼项的XPBD d_lambda，grads与p_indexes一一对应。
+//重写calculate_d_lambdas的约束组也应使用此公式，保持与默认实现一致
+pub fn damped_d_lambda(
+    c_data: &ConstraintData,
+    c_value: f32,
+    grads: &[Vector3<f32>],
+    p_indexes: &[usize],
+    particles_data: &[ParticleData],
+) -> f32 {
+    let a = c_data.alpha;
+    let gamma = c_data.gamma;
+    let mut sum_gwg = 0.;
+    //阻尼项 grad·(x - x_prev)
+    let mut sum_gdx = 0.;
+    for (grad, p_index) in grads.iter().zip(p_indexes) {
+        let p_data = &particles_data[*p_index];
+        sum_gwg += p_data.w * grad.dot(grad).min(f32::MAX).max(f32::MIN);
+        sum_gdx += grad.dot(&(p_data.predict_position - p_data.position));
+    }
+    return -(a * c_data.lambda + c_value + gamma * sum_gdx)
+        / ((1.0 + gamma) * sum_gwg + a).max(0.0001).min(f32::MAX);
+}
+
 pub trait ConstraintsSolver {
     fn initialize(&mut self, particles_data: &[ParticleData]);
     fn calculate_constraints_gradients(&mut self, particles_data: &mut [ParticleData]);
     fn clear_lambdas(&mut self);
     fn update_lambdas(&mut self);
     fn calculate_cache(&mut self, particles_data: &mut [ParticleData], dt: f32);
+    fn mark_cache_dirty(&mut self);
     fn calculate_d_lambdas(&mut self, particles_data: &mut [ParticleData]);
+    //限制不等式约束的d_lambda，使累计的lambda保持在允许的范围内
+    fn clamp_d_lambdas(&mut self, particles_data: &[ParticleData]);
     fn calculate_d_positions(&mut self, particles_data: &mut [ParticleData]);
+    //收集由距离约束直接相连的质点对，质点对以(小索引, 大索引)表示
+    fn collect_connected_pairs(&self, pairs: &mut HashSet<(usize, usize)>);
+    //收集距离约束构成的边及其静止长度
+    fn collect_rest_edges(&self, edges: &mut Vec<(usize, usize, f32)>);
+    //依次访问每个约束引用的质点索引
+    fn for_each_pindexes(&self, f: &mut dyn FnMut(&[usize]));
+    //约束组数量，元组为各元素之和
+    fn group_count(&self) -> u32;
+    //移除超过断裂阈值的约束并记录事件，group为第一个约束组的编号
+    fn break_constraints(
+        &mut self,
+        particles_data: &[ParticleData],
+        dt: f32,
+        group: u32,
+        events: &mut Vec<BreakEvent>,
+    );
+    //第group组约束的数量，group超出范围时返回0
+    fn constraint_count(&self, group: u32) -> usize;
+    //按当前顺序写入第group组约束的id与约束力，返回写入的数量
+    fn copy_forces(&self, group: u32, dt: f32, forces: &mut [ConstraintForce]) -> usize;
+    //按remap(旧索引->新索引)更新约束引用的质点，引用了被移除质点(None)的约束会被移除
+    fn remap_particles(&mut self, remap: &[Option<usize>]);
+    //将质点from在normal正方向一侧的约束改为引用质点to，返回被修改的约束数量
+    fn split_particle(
+        &mut self,
+        particles_data: &[ParticleData],
+        from: usize,
+        to: usize,
+        normal: &Vector3<f32>,
+    ) -> usize;
 
-    fn solve(&mut self, particles_data: &mut [ParticleData], dt: f32, iterate_count: u32) {
+    //每个子步开始求解前调用一次
+    fn begin_solve(&mut self, particles_data: &mut [ParticleData], dt: f32) {
         //clear lambdas to 0.0
         self.clear_lambdas();
         //calculate caches if not
         self.calculate_cache(particles_data, dt);
-        //begin solve iterations
-        for _ in 0..iterate_count {
-            self.calculate_constraints_gradients(particles_data);
-            //calculate delta lambdas
-            self.calculate_d_lambdas(particles_data);
-            //calculate delta positions
-            particles_data.clear_d_positions();
-            self.calculate_d_positions(particles_data);
-            //update predict positions
-            for p in particles_data.iter_mut() {
-                p.predict_position += p.d_position;
-            }
-            //update lambdas
-            self.update_lambdas();
+    }
+
+    //一次求解迭代，便于与刚体等其它求解器在同一迭代循环中交替求解
+    fn solve_iteration(&mut self, particles_data: &mut [ParticleData]) {
+        self.calculate_constraints_gradients(particles_data);
+        //calculate delta lambdas
+        self.calculate_d_lambdas(particles_data);
+        self.clamp_d_lambdas(particles_data);
+        //calculate delta positions
+        particles_data.clear_d_positions();
+        self.calculate_d_positions(particles_data);
+        //update predict positions
+        for p in particles_data.iter_mut() {
+            p.predict_position += p.d_position;
         }
+        //update lambdas
+        self.update_lambdas();
     }
 }
 
@@ -52,56 +116,173 @@ where
         }
     }
 
-    fn calculate_constraints_gradients(&mut self, particles_data: &mut [ParticleData]) {
+    default fn calculate_constraints_gradients(&mut self, particles_data: &mut [ParticleData]) {
         for (c_index, cs) in self.defines.iter().enumerate() {
-            let c_data = &mut self.datas[c_index];
-            cs.get_grad(particles_data, &mut c_data.grads);
+            let offset = self.datas[c_index].grad_offset;
+            let grads = &mut self.grads[offset..offset + cs.get_constraint_particle_count()];
+            cs.get_grad(particles_data, grads);
         }
     }
 
-    fn clear_lambdas(&mut self) {
+    default fn clear_lambdas(&mut self) {
         for e in self.datas.iter_mut() {
             e.lambda = 0.
         }
     }
     default fn calculate_d_lambdas(&mut self, particles_data: &mut [ParticleData]) {
         for (c_index, cs) in self.defines.iter().enumerate() {
-            let c_data = &mut self.datas[c_index];
+            let c_data = &self.datas[c_index];
             let c_value = cs.get_value(particles_data);
-            let a = c_data.alpha;
-            let lambda = c_data.lambda;
-            let mut sum_gwg = 0.;
-            for (g_index, p_index) in cs.get_pindexes().iter().enumerate() {
-                let grad = &c_data.grads[g_index];
-                let p_data = &particles_data[*p_index];
-                sum_gwg += p_data.w * grad.dot(grad).min(f32::MAX).max(f32::MIN);
-            }
-            let d_lambda = -(a * lambda + c_value) / (sum_gwg + a).max(0.0001).min(f32::MAX);
+            let grads = &self.grads[self.grad_range(c_index)];
+            let d_lambda =
+                damped_d_lambda(c_data, c_value, grads, cs.get_pindexes(), particles_data);
             self.datas[c_index].d_lambda = d_lambda;
         }
     }
-    fn update_lambdas(&mut self) {
+    default fn update_lambdas(&mut self) {
         for c in self.datas.iter_mut() {
             c.lambda += c.d_lambda;
         }
     }
 
-    fn calculate_cache(&mut self, particles_data: &mut [ParticleData], dt: f32) {
+    fn clamp_d_lambdas(&mut self, particles_data: &[ParticleData]) {
+        for (cs, c) in self.defines.iter().zip(self.datas.iter_mut()) {
+            let bound = cs.get_lambda_bound(particles_data);
+            if bound != LambdaBound::Free {
+                c.d_lambda = bound.clamp(c.lambda + c.d_lambda) - c.lambda;
+            }
+        }
+    }
+
+    default fn calculate_cache(&mut self, particles_data: &mut [ParticleData], dt: f32) {
         ConstraintsData::calculate_cache(self, particles_data, dt);
     }
 
-    fn calculate_d_positions(&mut self, particles_data: &mut [ParticleData]) {
+    fn mark_cache_dirty(&mut self) {
+        ConstraintsData::mark_cache_dirty(self);
+    }
+
+    default fn calculate_d_positions(&mut self, particles_data: &mut [ParticleData]) {
         // self.datas.print_d_lambdas();
         for (c_index, c) in self.defines.iter().enumerate() {
             let c_data = &mut self.datas[c_index];
             for (idx, p_index) in c.get_pindexes().iter().enumerate() {
                 let p_data = &mut particles_data[*p_index];
-                let grad = &c_data.grads[idx];
+                let grad = &self.grads[c_data.grad_offset + idx];
                 let d = p_data.w * c_data.d_lambda * grad;
                 p_data.d_position += d;
             }
         }
     }
+
+    default fn collect_connected_pairs(&self, _: &mut HashSet<(usize, usize)>) {}
+
+    default fn collect_rest_edges(&self, _: &mut Vec<(usize, usize, f32)>) {}
+
+    fn for_each_pindexes(&self, f: &mut dyn FnMut(&[usize])) {
+        for c in self.defines.iter() {
+            f(c.get_pindexes());
+        }
+    }
+
+    fn group_count(&self) -> u32 {
+        return 1;
+    }
+
+    fn constraint_count(&self, group: u32) -> usize {
+        if group != 0 {
+            return 0;
+        }
+        return self.datas.len();
+    }
+
+    fn copy_forces(&self, group: u32, dt: f32, forces: &mut [ConstraintForce]) -> usize {
+        if group != 0 {
+            return 0;
+        }
+        let count = forces.len().min(self.datas.len());
+        for (out, data) in forces.iter_mut().zip(self.datas.iter()) {
+            *out = ConstraintForce {
+                id: data.id,
+                force: data.lambda / (dt * dt),
+            };
+        }
+        return count;
+    }
+
+    fn break_constraints(
+        &mut self,
+        particles_data: &[ParticleData],
+        dt: f32,
+        group: u32,
+        events: &mut Vec<BreakEvent>,
+    ) {
+        //倒序遍历，swap_remove不会影响未检查的约束
+        for index in (0..self.defines.len()).rev() {
+            let cs = &self.defines[index];
+            let threshold = cs.get_break_threshold();
+            if threshold.kind().unwrap_or(BreakKind::None) == BreakKind::None {
+                continue;
+            }
+            let force = self.datas[index].lambda.abs() / (dt * dt);
+            if !threshold.is_exceeded(cs.get_strain(particles_data), force) {
+                continue;
+            }
+            let p_indexes = cs.get_pindexes();
+            let p = |i: usize| p_indexes.get(i).map_or(NO_PARTICLE, |p| *p as u32);
+            events.push(BreakEvent {
+                group: group,
+                id: self.datas[index].id,
+                p1: p(0),
+                p2: p(1),
+                new_particle: NO_PARTICLE,
+            });
+            self.swap_remove(index);
+        }
+    }
+
+    fn remap_particles(&mut self, remap: &[Option<usize>]) {
+        for index in (0..self.defines.len()).rev() {
+            let p_indexes = self.defines[index].get_pindexes_mut();
+            if p_indexes.iter().any(|p| remap[*p].is_none()) {
+                self.swap_remove(index);
+                continue;
+            }
+            for p in p_indexes.iter_mut() {
+                *p = remap[*p].unwrap();
+            }
+        }
+    }
+
+    fn split_particle(
+        &mut self,
+        particles_data: &[ParticleData],
+        from: usize,
+        to: usize,
+        normal: &Vector3<f32>,
+    ) -> usize {
+        let origin = &particles_data[from].predict_position;
+        let mut count = 0;
+        for cs in self.defines.iter_mut() {
+            let p_indexes = cs.get_pindexes_mut();
+            if !p_indexes.contains(&from) {
+                continue;
+            }
+            //以约束中其它质点的中心判断约束位于哪一侧
+            let others = p_indexes
+                .iter()
+                .filter(|p| **p != from)
+                .map(|p| &particles_data[*p].predict_position);
+            if !is_on_split_side(origin, others, normal) {
+                continue;
+            }
+            for p_index in p_indexes.iter_mut().filter(|p| **p == from) {
+                *p_index = to;
+            }
+            count += 1;
+        }
+        return count;
+    }
 }
 
 impl<T> ConstraintsSolver for &mut T
@@ -123,10 +304,18 @@ where
         (self as &mut T).update_lambdas();
     }
 
+    fn clamp_d_lambdas(&mut self, particles_data: &[ParticleData]) {
+        (self as &mut T).clamp_d_lambdas(particles_data);
+    }
+
     fn calculate_cache(&mut self, particles_data: &mut [ParticleData], dt: f32) {
         (self as &mut T).calculate_cache(particles_data, dt);
     }
 
+    fn mark_cache_dirty(&mut self) {
+        (self as &mut T).mark_cache_dirty();
+    }
+
     fn calculate_d_lambdas(&mut self, particles_data: &mut [ParticleData]) {
         (self as &mut T).calculate_d_lambdas(particles_data);
     }
@@ -134,6 +323,54 @@ where
     fn calculate_d_positions(&mut self, particles_data: &mut [ParticleData]) {
         (self as &mut T).calculate_d_positions(particles_data);
     }
+
+    fn group_count(&self) -> u32 {
+        return (self as &T).group_count();
+    }
+
+    fn constraint_count(&self, group: u32) -> usize {
+        return (self as &T).constraint_count(group);
+    }
+
+    fn copy_forces(&self, group: u32, dt: f32, forces: &mut [ConstraintForce]) -> usize {
+        return (self as &T).copy_forces(group, dt, forces);
+    }
+
+    fn break_constraints(
+        &mut self,
+        particles_data: &[ParticleData],
+        dt: f32,
+        group: u32,
+        events: &mut Vec<BreakEvent>,
+    ) {
+        (self as &mut T).break_constraints(particles_data, dt, group, events);
+    }
+
+    fn remap_particles(&mut self, remap: &[Option<usize>]) {
+        (self as &mut T).remap_particles(remap);
+    }
+
+    fn split_particle(
+        &mut self,
+        particles_data: &[ParticleData],
+        from: usize,
+        to: usize,
+        normal: &Vector3<f32>,
+    ) -> usize {
+        return (self as &mut T).split_particle(particles_data, from, to, normal);
+    }
+
+    fn collect_connected_pairs(&self, pairs: &mut HashSet<(usize, usize)>) {
+        (self as &T).collect_connected_pairs(pairs);
+    }
+
+    fn collect_rest_edges(&self, edges: &mut Vec<(usize, usize, f32)>) {
+        (self as &T).collect_rest_edges(edges);
+    }
+
+    fn for_each_pindexes(&self, f: &mut dyn FnMut(&[usize])) {
+        (self as &T).for_each_pindexes(f);
+    }
 }
 
 macro_rules! define_constraints_solver {
@@ -165,12 +402,24 @@ macro_rules! define_constraints_solver {
                 )*
             }
 
+            fn clamp_d_lambdas(&mut self, particles_data: &[ParticleData]) {
+                $(
+                    self.$index.clamp_d_lambdas(particles_data);
+                )*
+            }
+
             fn calculate_cache(&mut self, particles_data: &mut [ParticleData], dt: f32) {
                 $(
                     self.$index.calculate_cache(particles_data, dt);
                 )*
             }
 
+            fn mark_cache_dirty(&mut self) {
+                $(
+                    self.$index.mark_cache_dirty();
+                )*
+            }
+
             fn calculate_d_lambdas(&mut self, particles_data: &mut [ParticleData]) {
                 $(
                     self.$index.calculate_d_lambdas(particles_data);
@@ -182,6 +431,81 @@ macro_rules! define_constraints_solver {
                     self.$index.calculate_d_positions(particles_data);
                 )*
             }
+
+            fn collect_connected_pairs(&self, pairs: &mut HashSet<(usize, usize)>) {
+                $(
+                    self.$index.collect_connected_pairs(pairs);
+                )*
+            }
+
+            fn collect_rest_edges(&self, edges: &mut Vec<(usize, usize, f32)>) {
+                $(
+                    self.$index.collect_rest_edges(edges);
+                )*
+            }
+
+            fn for_each_pindexes(&self, f: &mut dyn FnMut(&[usize])) {
+                $(
+                    self.$index.for_each_pindexes(f);
+                )*
+            }
+
+            fn group_count(&self) -> u32 {
+                return 0 $(+ self.$index.group_count())*;
+            }
+
+            fn constraint_count(&self, group: u32) -> usize {
+                let mut group = group;
+                $(
+                    if group < self.$index.group_count() {
+                        return self.$index.constraint_count(group);
+                    }
+                    group -= self.$index.group_count();
+                )*
+                return 0;
+            }
+
+            fn copy_forces(&self, group: u32, dt: f32, forces: &mut [ConstraintForce]) -> usize {
+                let mut group = group;
+                $(
+                    if group < self.$index.group_count() {
+                        return self.$index.copy_forces(group, dt, forces);
+                    }
+                    group -= self.$index.group_count();
+                )*
+                return 0;
+            }
+
+            fn break_constraints(
+                &mut self,
+                particles_data: &[ParticleData],
+                dt: f32,
+                group: u32,
+                events: &mut Vec<BreakEvent>,
+            ) {
+                let mut group = group;
+                $(
+                    self.$index.break_constraints(particles_data, dt, group, events);
+                    group += self.$index.group_count();
+                )*
+                let _ = group;
+            }
+
+            fn remap_particles(&mut self, remap: &[Option<usize>]) {
+                $(
+                    self.$index.remap_particles(remap);
+                )*
+            }
+
+            fn split_particle(
+                &mut self,
+                particles_data: &[ParticleData],
+                from: usize,
+                to: usize,
+                normal: &Vector3<f32>,
+            ) -> usize {
+                return 0 $(+ self.$index.split_particle(particles_data, from, to, normal))*;
+            }
         }
     };
 }
@@ -189,3 +513,8 @@ macro_rules! define_constraints_solver {
 define_constraints_solver!(A, 0, B, 1);
 define_constraints_solver!(A, 0, B, 1, C, 2);
 define_constraints_solver!(A, 0, B, 1, C, 2, D, 3);
+define_constraints_solver!(A, 0, B, 1, C, 2, D, 3, E, 4);
+define_constraints_solver!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5);
+define_constraints_solver!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6);
+define_constraints_solver!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7);
+define_constraints_solver!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8);
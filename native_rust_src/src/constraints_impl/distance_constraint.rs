@@ -1,7 +1,9 @@
+use std::collections::HashSet;
+
 use nalgebra::{distance, Point3, Vector3};
 
 use crate::constraints::{
-    constraint::{Constraint, ConstraintsData},
+    constraint::{BreakThreshold, Constraint, ConstraintsData},
     particle::ParticleData,
     solver::ConstraintsSolver,
 };
@@ -11,6 +13,8 @@ pub struct DistanceConstraint {
     p_indexes: [usize; 2],
     stiffness_inv: f32,
     rest: f32,
+    break_threshold: BreakThreshold,
+    damping: f32,
 }
 
 impl DistanceConstraint {
@@ -19,9 +23,30 @@ impl DistanceConstraint {
             p_indexes: [p1, p2],
             stiffness_inv: stiffness_inv,
             rest: 0.0,
+            break_threshold: BreakThreshold::NONE,
+            damping: 0.0,
+        };
+    }
+
+    //使用给定的静止长度，不再由on_initialize计算
+    pub fn with_rest(p1: usize, p2: usize, stiffness_inv: f32, rest: f32) -> DistanceConstraint {
+        return DistanceConstraint {
+            p_indexes: [p1, p2],
+            stiffness_inv: stiffness_inv,
+            rest: rest,
+            break_threshold: BreakThreshold::NONE,
+            damping: 0.0,
         };
     }
 
+    pub fn set_break_threshold(&mut self, threshold: BreakThreshold) {
+        self.break_threshold = threshold;
+    }
+
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping;
+    }
+
     fn distance(&self, particles: &[ParticleData]) -> f32 {
         let p1 = particles[self.p_indexes[0]].predict_position;
         let p2 = particles[self.p_indexes[1]].predict_position;
@@ -49,7 +74,24 @@ impl Constraint for DistanceConstraint {
     fn get_pindexes(&self) -> &[usize] {
         return &self.p_indexes;
     }
-    fn get_constraint_particle_count() -> usize {
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_damping(&self) -> f32 {
+        return self.damping;
+    }
+    fn get_break_threshold(&self) -> BreakThreshold {
+        return self.break_threshold;
+    }
+    //相对于静止长度的伸长比例
+    fn get_strain(&self, particles: &[ParticleData]) -> f32 {
+        let value = self.get_value(particles).abs();
+        if self.rest > 0.0 {
+            return value / self.rest;
+        }
+        return value;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
         return 2;
     }
 }
@@ -63,15 +105,39 @@ impl ConstraintsSolver for ConstraintsData<DistanceConstraint> {
             let c_data = &mut self.datas[c_index];
             let c_value = cs.get_value(particles_data);
             let a = c_data.alpha;
+            let gamma = c_data.gamma;
             let lambda = c_data.lambda;
             let mut sum_gwg = 0.;
-            for (g_index, p_index) in cs.get_pindexes().iter().enumerate() {
-                let grad = &c_data.grads[g_index];
+            for p_index in cs.get_pindexes().iter() {
                 let p_data = &particles_data[*p_index];
                 sum_gwg += p_data.w;
             }
-            let d_lambda = -(a * lambda + c_value) / (sum_gwg + a);
+            //梯度即两质点连线方向，阻尼项为沿连线的相对位移
+            let mut damping = 0.;
+            if gamma != 0.0 {
+                let p0 = &particles_data[cs.p_indexes[0]];
+                let p1 = &particles_data[cs.p_indexes[1]];
+                let grad = self.grads[c_data.grad_offset];
+                let dx0 = p0.predict_position - p0.position;
+                let dx1 = p1.predict_position - p1.position;
+                damping = gamma * grad.dot(&(dx0 - dx1));
+            }
+            let d_lambda = -(a * lambda + c_value + damping) / ((1.0 + gamma) * sum_gwg + a);
             self.datas[c_index].d_lambda = d_lambda;
         }
     }
+
+    fn collect_connected_pairs(&self, pairs: &mut HashSet<(usize, usize)>) {
+        for cs in self.defines.iter() {
+            let [p1, p2] = cs.p_indexes;
+            pairs.insert((p1.min(p2), p1.max(p2)));
+        }
+    }
+
+    fn collect_rest_edges(&self, edges: &mut Vec<(usize, usize, f32)>) {
+        for cs in self.defines.iter() {
+            let [p1, p2] = cs.p_indexes;
+            edges.push((p1, p2, cs.rest));
+        }
+    }
 }
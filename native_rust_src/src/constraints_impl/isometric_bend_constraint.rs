@@ -0,0 +1,234 @@
+use nalgebra::{Matrix3, Vector3};
+
+use crate::constraints::{
+    constraint::{BreakThreshold, Constraint, ConstraintsData},
+    particle::ParticleData,
+    solver::ConstraintsSolver,
+};
+
+//三角形网格弯曲模型，创建时由options选择
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BendingModel {
+    //基于二面角，适合大变形，近似平面时梯度奇异
+    Dihedral = 0,
+    //等距弯曲(Bergou 2006)，能量为质点位置的二次型，适合近似平面的布料
+    Isometric = 1,
+}
+
+impl BendingModel {
+    //C#传入的值不在枚举范围内时返回None
+    pub fn from_u32(value: u32) -> Option<BendingModel> {
+        return match value {
+            0 => Some(BendingModel::Dihedral),
+            1 => Some(BendingModel::Isometric),
+            _ => None,
+        };
+    }
+}
+
+//等距弯曲约束，p_indexes与TriangleBendConstraint相同：
+//p_indexes[0]、p_indexes[1]为公共边，p_indexes[2]、p_indexes[3]为两侧的顶点
+//v = sum(K_i * x_i)为公共边处的离散拉普拉斯，静止状态为平面时为0
+//C = s * (v - v_rest)为三维向量约束，s = sqrt(3 / (A0 + A1))，梯度s * K_i * I为常量，
+//|C|^2 / 2即Bergou的二次弯曲能量。v_rest保存在由公共边与法线构成的局部标架中，随网格一起旋转，
+//标架按子步开始时的位置计算，使子步内的约束保持线性
+#[repr(C)]
+#[derive(Debug)]
+pub struct IsometricBendConstraint {
+    pub p_indexes: [usize; 4],
+    pub stiffness_inv: f32,
+    break_threshold: BreakThreshold,
+    damping: f32,
+    //以下由on_initialize按静止状态计算
+    k: [f32; 4],
+    scale: f32,
+    rest: Vector3<f32>,
+    //向量约束的lambda，每个子步开始时清零
+    lambda: Vector3<f32>,
+    d_lambda: Vector3<f32>,
+    //最近一次求解时约束向量C的单位方向，lambda在其上的投影作为标量lambda
+    direction: Vector3<f32>,
+}
+
+fn cot(a: &Vector3<f32>, b: &Vector3<f32>) -> f32 {
+    let sin = a.cross(b).norm();
+    if sin <= f32::EPSILON {
+        return 0.0;
+    }
+    return a.dot(b) / sin;
+}
+
+impl IsometricBendConstraint {
+    pub fn new(p_indexes: [usize; 4], stiffness_inv: f32) -> IsometricBendConstraint {
+        return IsometricBendConstraint {
+            p_indexes: p_indexes,
+            stiffness_inv: stiffness_inv,
+            break_threshold: BreakThreshold::NONE,
+            damping: 0.0,
+            k: [0.0; 4],
+            scale: 0.0,
+            rest: Vector3::zeros(),
+            lambda: Vector3::zeros(),
+            d_lambda: Vector3::zeros(),
+            direction: Vector3::zeros(),
+        };
+    }
+
+    pub fn set_break_threshold(&mut self, threshold: BreakThreshold) {
+        self.break_threshold = threshold;
+    }
+
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping;
+    }
+
+    fn laplacian(&self, particles_data: &[ParticleData]) -> Vector3<f32> {
+        let mut v = Vector3::zeros();
+        for (i, p_index) in self.p_indexes.iter().enumerate() {
+            v += particles_data[*p_index].predict_position.coords * self.k[i];
+        }
+        return v;
+    }
+
+    //列向量依次为公共边方向、第一个三角形的法线与副法线，退化时返回单位矩阵
+    fn local_frame(&self, particles_data: &[ParticleData]) -> Matrix3<f32> {
+        let x0 = &particles_data[self.p_indexes[0]].position;
+        let e0 = particles_data[self.p_indexes[1]].position - x0;
+        let e1 = particles_data[self.p_indexes[2]].position - x0;
+        let t = match e0.try_normalize(f32::EPSILON) {
+            Some(t) => t,
+            None => return Matrix3::identity(),
+        };
+        let n = match e0.cross(&e1).try_normalize(f32::EPSILON) {
+            Some(n) => n,
+            None => return Matrix3::identity(),
+        };
+        return Matrix3::from_columns(&[t, n, t.cross(&n)]);
+    }
+
+    fn vector_value(&self, particles_data: &[ParticleData]) -> Vector3<f32> {
+        let rest = self.local_frame(particles_data) * self.rest;
+        return (self.laplacian(particles_data) - rest) * self.scale;
+    }
+
+    fn calculate_d_lambda(
+        &mut self,
+        alpha: f32,
+        gamma: f32,
+        particles_data: &[ParticleData],
+    ) -> Vector3<f32> {
+        let c_value = self.vector_value(particles_data);
+        if let Some(direction) = c_value.try_normalize(f32::EPSILON) {
+            self.direction = direction;
+        }
+        //梯度为s * K_i * I，三个分量互不耦合，分母为常量
+        let mut sum_gwg = 0.;
+        let mut sum_gdx = Vector3::zeros();
+        for (i, p_index) in self.p_indexes.iter().enumerate() {
+            let p_data = &particles_data[*p_index];
+            let g = self.scale * self.k[i];
+            sum_gwg += p_data.w * g * g;
+            sum_gdx += (p_data.predict_position - p_data.position) * g;
+        }
+        let denominator = (1.0 + gamma) * sum_gwg + alpha;
+        if denominator == 0.0 {
+            return Vector3::zeros();
+        }
+        return -(self.lambda * alpha + c_value + sum_gdx * gamma) / denominator;
+    }
+}
+
+impl Constraint for IsometricBendConstraint {
+    fn on_initialize(&mut self, particles_data: &[ParticleData]) {
+        let x0 = &particles_data[self.p_indexes[0]].predict_position;
+        let x1 = &particles_data[self.p_indexes[1]].predict_position;
+        let x2 = &particles_data[self.p_indexes[2]].predict_position;
+        let x3 = &particles_data[self.p_indexes[3]].predict_position;
+        let e0 = x1 - x0;
+        let e1 = x2 - x0;
+        let e2 = x3 - x0;
+        let e3 = x2 - x1;
+        let e4 = x3 - x1;
+        let c01 = cot(&e0, &e1);
+        let c02 = cot(&e0, &e2);
+        let c03 = cot(&-e0, &e3);
+        let c04 = cot(&-e0, &e4);
+        self.k = [c03 + c04, c01 + c02, -c01 - c03, -c02 - c04];
+        let area = 0.5 * (e0.cross(&e1).norm() + e0.cross(&e2).norm());
+        self.scale = if area > f32::EPSILON {
+            (3.0 / area).sqrt()
+        } else {
+            0.0
+        };
+        self.rest = self.local_frame(particles_data).transpose() * self.laplacian(particles_data);
+    }
+    fn get_stiffness_inv(&self) -> f32 {
+        return self.stiffness_inv;
+    }
+    //向量约束的长度，用于断裂判断与调试
+    fn get_value(&self, particles_data: &[ParticleData]) -> f32 {
+        return self.vector_value(particles_data).norm();
+    }
+    fn get_grad(&self, particles_data: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        let n = self
+            .vector_value(particles_data)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vector3::zeros());
+        for (grad, k) in out_grads.iter_mut().zip(self.k.iter()) {
+            *grad = n * (self.scale * k);
+        }
+    }
+    fn get_pindexes(&self) -> &[usize] {
+        return &self.p_indexes;
+    }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_damping(&self) -> f32 {
+        return self.damping;
+    }
+    fn get_break_threshold(&self) -> BreakThreshold {
+        return self.break_threshold;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
+        return 4;
+    }
+}
+
+//lambda为三维向量，保存在约束自身中；ConstraintData::lambda记录其在C方向上的投影，
+//与get_grad的方向一致，用于约束力与断裂判断
+impl ConstraintsSolver for ConstraintsData<IsometricBendConstraint> {
+    //梯度为常量，直接由k与scale给出
+    fn calculate_constraints_gradients(&mut self, _: &mut [ParticleData]) {}
+
+    fn clear_lambdas(&mut self) {
+        for (cs, c_data) in self.defines.iter_mut().zip(self.datas.iter_mut()) {
+            cs.lambda = Vector3::zeros();
+            cs.direction = Vector3::zeros();
+            c_data.lambda = 0.0;
+        }
+    }
+
+    fn calculate_d_lambdas(&mut self, particles_data: &mut [ParticleData]) {
+        for (cs, c_data) in self.defines.iter_mut().zip(self.datas.iter()) {
+            cs.d_lambda = cs.calculate_d_lambda(c_data.alpha, c_data.gamma, particles_data);
+        }
+    }
+
+    fn calculate_d_positions(&mut self, particles_data: &mut [ParticleData]) {
+        for cs in self.defines.iter() {
+            for (p_index, k) in cs.p_indexes.iter().zip(cs.k.iter()) {
+                let p_data = &mut particles_data[*p_index];
+                p_data.d_position += cs.d_lambda * (p_data.w * cs.scale * k);
+            }
+        }
+    }
+
+    fn update_lambdas(&mut self) {
+        for (cs, c_data) in self.defines.iter_mut().zip(self.datas.iter_mut()) {
+            cs.lambda += cs.d_lambda;
+            c_data.lambda = cs.lambda.dot(&cs.direction);
+        }
+    }
+}
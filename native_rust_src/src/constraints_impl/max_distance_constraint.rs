@@ -0,0 +1,82 @@
+use nalgebra::{distance, Vector3};
+
+use crate::constraints::{
+    constraint::{Constraint, ConstraintsData, LambdaBound},
+    particle::ParticleData,
+    solver::ConstraintsSolver,
+};
+
+//绳索约束，两质点距离不超过max，只阻止拉伸不阻止压缩
+#[repr(C)]
+pub struct MaxDistanceConstraint {
+    p_indexes: [usize; 2],
+    stiffness_inv: f32,
+    max: f32,
+}
+
+impl MaxDistanceConstraint {
+    //最大距离由on_initialize按当前距离计算
+    pub fn new(p1: usize, p2: usize, stiffness_inv: f32) -> MaxDistanceConstraint {
+        return MaxDistanceConstraint::with_max(p1, p2, stiffness_inv, -1.0);
+    }
+
+    //max小于0时由on_initialize按当前距离计算
+    pub fn with_max(p1: usize, p2: usize, stiffness_inv: f32, max: f32) -> MaxDistanceConstraint {
+        return MaxDistanceConstraint {
+            p_indexes: [p1, p2],
+            stiffness_inv: stiffness_inv,
+            max: max,
+        };
+    }
+
+    fn distance(&self, particles: &[ParticleData]) -> f32 {
+        let p1 = &particles[self.p_indexes[0]].predict_position;
+        let p2 = &particles[self.p_indexes[1]].predict_position;
+        return distance(p1, p2);
+    }
+}
+
+impl Constraint for MaxDistanceConstraint {
+    fn on_initialize(&mut self, particles_data: &[ParticleData]) {
+        if self.max < 0.0 {
+            self.max = self.distance(particles_data);
+        }
+    }
+    fn get_stiffness_inv(&self) -> f32 {
+        return self.stiffness_inv;
+    }
+    fn get_value(&self, particles: &[ParticleData]) -> f32 {
+        return self.distance(particles) - self.max;
+    }
+    fn get_grad(&self, particles: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        let p0 = &particles[self.p_indexes[0]].predict_position;
+        let p1 = &particles[self.p_indexes[1]].predict_position;
+        let n = (p0 - p1)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vector3::zeros());
+        out_grads[0] = n;
+        out_grads[1] = -n;
+    }
+    fn get_lambda_bound(&self, _: &[ParticleData]) -> LambdaBound {
+        return LambdaBound::NonPositive;
+    }
+    fn get_pindexes(&self) -> &[usize] {
+        return &self.p_indexes;
+    }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
+        return 2;
+    }
+}
+
+impl ConstraintsSolver for ConstraintsData<MaxDistanceConstraint> {
+    //绳索以最大距离作为长程连接约束的边长
+    fn collect_rest_edges(&self, edges: &mut Vec<(usize, usize, f32)>) {
+        for cs in self.defines.iter() {
+            let [p1, p2] = cs.p_indexes;
+            edges.push((p1, p2, cs.max));
+        }
+    }
+}
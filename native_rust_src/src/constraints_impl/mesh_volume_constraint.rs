@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+
+use crate::constraints::{constraint::Constraint, particle::ParticleData};
+
+//封闭网格的整体体积(压强)约束，目标体积为初始体积乘以pressure
+pub struct MeshVolumeConstraint {
+    p_indexes: Vec<usize>,
+    //三角形顶点在p_indexes中的下标
+    triangles: Vec<[usize; 3]>,
+    pressure: f32,
+    stiffness_inv: f32,
+    rest: f32,
+}
+
+impl MeshVolumeConstraint {
+    //triangles需构成朝外的封闭曲面
+    pub fn new(
+        triangles: &[[usize; 3]],
+        pressure: f32,
+        stiffness_inv: f32,
+    ) -> MeshVolumeConstraint {
+        let mut p_indexes = vec![];
+        let mut local_indexes = HashMap::new();
+        let mut local_triangles = Vec::with_capacity(triangles.len());
+        for t in triangles {
+            let mut local = [0; 3];
+            for (i, p_index) in t.iter().enumerate() {
+                local[i] = *local_indexes.entry(*p_index).or_insert_with(|| {
+                    p_indexes.push(*p_index);
+                    p_indexes.len() - 1
+                });
+            }
+            local_triangles.push(local);
+        }
+        return MeshVolumeConstraint {
+            p_indexes: p_indexes,
+            triangles: local_triangles,
+            pressure: pressure,
+            stiffness_inv: stiffness_inv,
+            rest: 0.0,
+        };
+    }
+
+    fn volume(&self, particles: &[ParticleData]) -> f32 {
+        let mut volume = 0.0;
+        for t in &self.triangles {
+            let p0 = &particles[self.p_indexes[t[0]]].predict_position.coords;
+            let p1 = &particles[self.p_indexes[t[1]]].predict_position.coords;
+            let p2 = &particles[self.p_indexes[t[2]]].predict_position.coords;
+            volume += p0.dot(&p1.cross(p2));
+        }
+        return volume / 6.0;
+    }
+}
+
+impl Constraint for MeshVolumeConstraint {
+    fn on_initialize(&mut self, particles_data: &[ParticleData]) {
+        self.rest = self.volume(particles_data);
+    }
+    fn get_stiffness_inv(&self) -> f32 {
+        return self.stiffness_inv;
+    }
+    fn get_value(&self, particles: &[ParticleData]) -> f32 {
+        return self.volume(particles) - self.pressure * self.rest;
+    }
+    fn get_grad(&self, particles: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        for g in out_grads.iter_mut() {
+            *g = Vector3::zeros();
+        }
+        for t in &self.triangles {
+            let p0 = &particles[self.p_indexes[t[0]]].predict_position.coords;
+            let p1 = &particles[self.p_indexes[t[1]]].predict_position.coords;
+            let p2 = &particles[self.p_indexes[t[2]]].predict_position.coords;
+            out_grads[t[0]] += p1.cross(p2) / 6.0;
+            out_grads[t[1]] += p2.cross(p0) / 6.0;
+            out_grads[t[2]] += p0.cross(p1) / 6.0;
+        }
+    }
+    fn get_pindexes(&self) -> &[usize] {
+        return &self.p_indexes;
+    }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
+        return self.p_indexes.len();
+    }
+}
@@ -1,2 +1,10 @@
 pub mod distance_constraint;
+pub mod isometric_bend_constraint;
+pub mod max_distance_constraint;
+pub mod mesh_volume_constraint;
+pub mod neo_hookean_constraint;
+pub mod range_distance_constraint;
+pub mod shape_matching_constraint;
+pub mod tet_volume_constraint;
+pub mod tether_constraint;
 pub mod triangle_bend_constraint;
@@ -0,0 +1,256 @@
+use nalgebra::{Matrix3, Vector3};
+
+use crate::constraints::{
+    constraint::{Constraint, ConstraintId, ConstraintsData},
+    particle::ParticleData,
+    solver::ConstraintsSolver,
+};
+
+//导入用的四面体材质参数，每个四面体会展开为偏量与静水压两个约束
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct NeoHookeanTet {
+    pub p_indexes: [usize; 4],
+    pub youngs_modulus: f32,
+    pub poisson_ratio: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NeoHookeanEnergy {
+    //C = sqrt(tr(F^T F))，alpha = 1 / (mu * V)
+    Deviatoric,
+    //C = det(F) - (1 + mu / lambda)，alpha = 1 / (lambda * V)
+    Hydrostatic,
+}
+
+//没有配对约束
+const NO_PAIR: ConstraintId = ConstraintId::MAX;
+
+//泊松比的取值范围，为0时lambda为0，静水压约束的静止值发散；接近0.5时不可压缩
+const MIN_POISSON_RATIO: f32 = 0.01;
+const MAX_POISSON_RATIO: f32 = 0.499;
+
+//A Constraint-based Formulation of Stable Neo-Hookean Materials (Macklin 2021)
+pub struct NeoHookeanTetConstraint {
+    p_indexes: [usize; 4],
+    energy: NeoHookeanEnergy,
+    //同一四面体另一个约束的id，约束被移除或重新排列后仍能找到
+    pair: ConstraintId,
+    mu: f32,
+    lambda: f32,
+    rest_inv: Matrix3<f32>,
+    stiffness_inv: f32,
+    //静止四面体退化或模量不为正时不参与求解，梯度为0
+    active: bool,
+}
+
+impl NeoHookeanTetConstraint {
+    pub fn new(
+        p_indexes: [usize; 4],
+        energy: NeoHookeanEnergy,
+        youngs_modulus: f32,
+        poisson_ratio: f32,
+    ) -> NeoHookeanTetConstraint {
+        //杨氏模量与泊松比转换为拉梅参数
+        let e = youngs_modulus;
+        let nu = poisson_ratio.clamp(MIN_POISSON_RATIO, MAX_POISSON_RATIO);
+        return NeoHookeanTetConstraint {
+            p_indexes: p_indexes,
+            energy: energy,
+            pair: NO_PAIR,
+            mu: e / (2.0 * (1.0 + nu)),
+            lambda: e * nu / ((1.0 + nu) * (1.0 - 2.0 * nu)),
+            rest_inv: Matrix3::identity(),
+            stiffness_inv: 0.0,
+            active: false,
+        };
+    }
+
+    //每个四面体展开为相邻的偏量与静水压约束并互相记录id，
+    //ConstraintsData由Vec创建时id即为下标
+    pub fn from_tets(tets: &[NeoHookeanTet]) -> Vec<NeoHookeanTetConstraint> {
+        let mut constraints = Vec::with_capacity(tets.len() * 2);
+        for (index, tet) in tets.iter().enumerate() {
+            let deviatoric_id = 2 * index as ConstraintId;
+            let mut deviatoric = Self::new(
+                tet.p_indexes,
+                NeoHookeanEnergy::Deviatoric,
+                tet.youngs_modulus,
+                tet.poisson_ratio,
+            );
+            let mut hydrostatic = Self::new(
+                tet.p_indexes,
+                NeoHookeanEnergy::Hydrostatic,
+                tet.youngs_modulus,
+                tet.poisson_ratio,
+            );
+            deviatoric.pair = deviatoric_id + 1;
+            hydrostatic.pair = deviatoric_id;
+            constraints.push(deviatoric);
+            constraints.push(hydrostatic);
+        }
+        return constraints;
+    }
+
+    fn edges(&self, particles: &[ParticleData]) -> Matrix3<f32> {
+        let p0 = &particles[self.p_indexes[0]].predict_position;
+        let p1 = &particles[self.p_indexes[1]].predict_position;
+        let p2 = &particles[self.p_indexes[2]].predict_position;
+        let p3 = &particles[self.p_indexes[3]].predict_position;
+        return Matrix3::from_columns(&[p1 - p0, p2 - p0, p3 - p0]);
+    }
+
+    //形变梯度 F = Ds * Dm^-1
+    fn deformation_gradient(&self, particles: &[ParticleData]) -> Matrix3<f32> {
+        return self.edges(particles) * self.rest_inv;
+    }
+}
+
+impl Constraint for NeoHookeanTetConstraint {
+    fn on_initialize(&mut self, particles_data: &[ParticleData]) {
+        let rest = self.edges(particles_data);
+        let volume = rest.determinant().abs() / 6.0;
+        let modulus = match self.energy {
+            NeoHookeanEnergy::Deviatoric => self.mu,
+            NeoHookeanEnergy::Hydrostatic => self.lambda,
+        };
+        //模量为0或静止体积为0时约束不产生作用，而不是变为无限刚硬
+        match rest.try_inverse() {
+            Some(rest_inv) if modulus * volume > 0.0 => {
+                self.rest_inv = rest_inv;
+                self.stiffness_inv = 1.0 / (modulus * volume);
+                self.active = true;
+            }
+            _ => {
+                self.rest_inv = Matrix3::identity();
+                self.stiffness_inv = 0.0;
+                self.active = false;
+            }
+        }
+    }
+    fn get_stiffness_inv(&self) -> f32 {
+        return self.stiffness_inv;
+    }
+    fn get_value(&self, particles: &[ParticleData]) -> f32 {
+        if !self.active {
+            return 0.0;
+        }
+        let f = self.deformation_gradient(particles);
+        return match self.energy {
+            NeoHookeanEnergy::Deviatoric => f.norm(),
+            NeoHookeanEnergy::Hydrostatic => f.determinant() - (1.0 + self.mu / self.lambda),
+        };
+    }
+    fn get_grad(&self, particles: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        if !self.active {
+            for grad in out_grads.iter_mut() {
+                *grad = Vector3::zeros();
+            }
+            return;
+        }
+        let f = self.deformation_gradient(particles);
+        let d_c_d_f = match self.energy {
+            NeoHookeanEnergy::Deviatoric => {
+                let norm = f.norm();
+                if norm > 0.0 {
+                    f / norm
+                } else {
+                    Matrix3::zeros()
+                }
+            }
+            NeoHookeanEnergy::Hydrostatic => {
+                let (f1, f2, f3) = (f.column(0), f.column(1), f.column(2));
+                Matrix3::from_columns(&[f2.cross(&f3), f3.cross(&f1), f1.cross(&f2)])
+            }
+        };
+        let g = d_c_d_f * self.rest_inv.transpose();
+        out_grads[1] = g.column(0).into();
+        out_grads[2] = g.column(1).into();
+        out_grads[3] = g.column(2).into();
+        out_grads[0] = -(out_grads[1] + out_grads[2] + out_grads[3]);
+    }
+    fn get_pindexes(&self) -> &[usize] {
+        return &self.p_indexes;
+    }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
+        return 4;
+    }
+}
+
+impl ConstraintsData<NeoHookeanTetConstraint> {
+    fn sum_gwg(&self, a: usize, b: usize, particles_data: &[ParticleData]) -> f32 {
+        let mut sum = 0.;
+        let (grads_a, grads_b) = (self.grad_range(a), self.grad_range(b));
+        for (g_index, p_index) in self.defines[a].p_indexes.iter().enumerate() {
+            let w = particles_data[*p_index].w;
+            let grad_a = &self.grads[grads_a.start + g_index];
+            sum += w * grad_a.dot(&self.grads[grads_b.start + g_index]);
+        }
+        return sum;
+    }
+
+    //偏量约束返回与之联立求解的静水压约束的下标
+    fn pair_of(&self, index: usize) -> Option<usize> {
+        let define = &self.defines[index];
+        let pair = self.index_of(define.pair)?;
+        if self.defines[pair].p_indexes != define.p_indexes {
+            return None;
+        }
+        return Some(pair);
+    }
+
+    fn solve_single(&mut self, index: usize, particles_data: &[ParticleData]) {
+        let data = &self.datas[index];
+        let c_value = self.defines[index].get_value(particles_data);
+        let sum_gwg = self.sum_gwg(index, index, particles_data);
+        let d_lambda = -(data.alpha * data.lambda + c_value) / (sum_gwg + data.alpha).max(0.0001);
+        self.datas[index].d_lambda = d_lambda;
+    }
+
+    fn solve_pair(&mut self, d: usize, h: usize, particles_data: &[ParticleData]) {
+        let (d_data, h_data) = (&self.datas[d], &self.datas[h]);
+        let b_d = -(self.defines[d].get_value(particles_data) + d_data.alpha * d_data.lambda);
+        let b_h = -(self.defines[h].get_value(particles_data) + h_data.alpha * h_data.lambda);
+        let a_dd = self.sum_gwg(d, d, particles_data) + d_data.alpha;
+        let a_hh = self.sum_gwg(h, h, particles_data) + h_data.alpha;
+        let a_dh = self.sum_gwg(d, h, particles_data);
+        let det = a_dd * a_hh - a_dh * a_dh;
+        let (d_lambda_d, d_lambda_h) = if det.abs() > 1e-12 {
+            (
+                (b_d * a_hh - b_h * a_dh) / det,
+                (a_dd * b_h - a_dh * b_d) / det,
+            )
+        } else {
+            (0., 0.)
+        };
+        self.datas[d].d_lambda = d_lambda_d;
+        self.datas[h].d_lambda = d_lambda_h;
+    }
+}
+
+impl ConstraintsSolver for ConstraintsData<NeoHookeanTetConstraint> {
+    //同一四面体的两个约束需要联立求解，分开求解时静止状态无法保持平衡。
+    //配对按id查找，约束被移除、断裂或重新映射后顺序变化也不影响
+    fn calculate_d_lambdas(&mut self, particles_data: &mut [ParticleData]) {
+        for index in 0..self.defines.len() {
+            if !self.defines[index].active {
+                self.datas[index].d_lambda = 0.0;
+                continue;
+            }
+            let pair = self
+                .pair_of(index)
+                .filter(|pair| self.defines[*pair].active);
+            match (self.defines[index].energy, pair) {
+                (NeoHookeanEnergy::Deviatoric, Some(pair)) => {
+                    self.solve_pair(index, pair, particles_data)
+                }
+                //由配对的偏量约束一并求解
+                (NeoHookeanEnergy::Hydrostatic, Some(_)) => {}
+                _ => self.solve_single(index, particles_data),
+            }
+        }
+    }
+}
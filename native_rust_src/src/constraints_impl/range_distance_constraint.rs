@@ -0,0 +1,123 @@
+use nalgebra::{distance, Vector3};
+
+use crate::constraints::{
+    constraint::{Constraint, ConstraintsData, LambdaBound},
+    particle::ParticleData,
+    solver::{damped_d_lambda, ConstraintsSolver},
+};
+
+//距离范围约束，两质点距离保持在[min, max]之间，范围内不产生任何作用。
+//min或max小于0时不限制该侧
+#[repr(C)]
+pub struct RangeDistanceConstraint {
+    p_indexes: [usize; 2],
+    stiffness_inv: f32,
+    min: f32,
+    max: f32,
+}
+
+impl RangeDistanceConstraint {
+    pub fn new(
+        p1: usize,
+        p2: usize,
+        stiffness_inv: f32,
+        min: f32,
+        max: f32,
+    ) -> RangeDistanceConstraint {
+        return RangeDistanceConstraint {
+            p_indexes: [p1, p2],
+            stiffness_inv: stiffness_inv,
+            min: min,
+            max: max,
+        };
+    }
+
+    //min与max都限制时min不能大于max
+    pub fn is_valid(&self) -> bool {
+        return self.min < 0.0 || self.max < 0.0 || self.min <= self.max;
+    }
+
+    fn distance(&self, particles: &[ParticleData]) -> f32 {
+        let p1 = &particles[self.p_indexes[0]].predict_position;
+        let p2 = &particles[self.p_indexes[1]].predict_position;
+        return distance(p1, p2);
+    }
+
+    //以范围中点划分，靠近哪一侧就按哪一侧的限制求解，两侧都不限制时返回None
+    fn active_limit(&self, d: f32) -> Option<(f32, LambdaBound)> {
+        let has_min = self.min >= 0.0;
+        let has_max = self.max >= 0.0;
+        return match (has_min, has_max) {
+            (true, true) if d * 2.0 > self.min + self.max => {
+                Some((self.max, LambdaBound::NonPositive))
+            }
+            (true, _) => Some((self.min, LambdaBound::NonNegative)),
+            (false, true) => Some((self.max, LambdaBound::NonPositive)),
+            (false, false) => None,
+        };
+    }
+}
+
+impl Constraint for RangeDistanceConstraint {
+    fn on_initialize(&mut self, _: &[ParticleData]) {}
+    fn get_stiffness_inv(&self) -> f32 {
+        return self.stiffness_inv;
+    }
+    fn get_value(&self, particles: &[ParticleData]) -> f32 {
+        let d = self.distance(particles);
+        return match self.active_limit(d) {
+            Some((limit, _)) => d - limit,
+            None => 0.0,
+        };
+    }
+    fn get_grad(&self, particles: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        let p0 = &particles[self.p_indexes[0]].predict_position;
+        let p1 = &particles[self.p_indexes[1]].predict_position;
+        let n = (p0 - p1)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vector3::zeros());
+        out_grads[0] = n;
+        out_grads[1] = -n;
+    }
+    fn get_lambda_bound(&self, particles: &[ParticleData]) -> LambdaBound {
+        return match self.active_limit(self.distance(particles)) {
+            Some((_, bound)) => bound,
+            None => LambdaBound::Free,
+        };
+    }
+    fn get_pindexes(&self) -> &[usize] {
+        return &self.p_indexes;
+    }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
+        return 2;
+    }
+}
+
+impl ConstraintsSolver for ConstraintsData<RangeDistanceConstraint> {
+    fn calculate_d_lambdas(&mut self, particles_data: &mut [ParticleData]) {
+        for (c_index, cs) in self.defines.iter().enumerate() {
+            let c_data = &mut self.datas[c_index];
+            //活动的一侧改变后，之前累计的lambda符号与新的限制相反，需要清零
+            let bound = cs.get_lambda_bound(particles_data);
+            if bound.clamp(c_data.lambda) != c_data.lambda {
+                c_data.lambda = 0.0;
+            }
+            let c_value = cs.get_value(particles_data);
+            let offset = c_data.grad_offset;
+            let grads = &self.grads[offset..offset + cs.p_indexes.len()];
+            c_data.d_lambda =
+                damped_d_lambda(c_data, c_value, grads, &cs.p_indexes, particles_data);
+        }
+    }
+
+    //只有限制了最大距离的约束才能作为长程连接约束的边
+    fn collect_rest_edges(&self, edges: &mut Vec<(usize, usize, f32)>) {
+        for cs in self.defines.iter().filter(|cs| cs.max >= 0.0) {
+            let [p1, p2] = cs.p_indexes;
+            edges.push((p1, p2, cs.max));
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use nalgebra::{Matrix3, Point3, Rotation3, Vector3};
+
+use crate::constraints::{
+    constraint::{Constraint, ConstraintsData},
+    particle::ParticleData,
+    solver::{damped_d_lambda, ConstraintsSolver},
+};
+
+//固定质点(w为0)视为质量极大，计算质心与旋转时以此代替无穷大
+const PINNED_W: f32 = 0.000001;
+const EPS: f32 = 0.000001;
+
+//Meshless Deformations Based on Shape Matching (Müller 2005)
+//C = sqrt(sum(m_i * |x_i - g_i|^2))，g_i为刚性变换后的初始形状，
+//stiffness_inv为0时一次迭代即可将质点投影到目标位置
+pub struct ShapeMatchingConstraint {
+    p_indexes: Vec<usize>,
+    stiffness_inv: f32,
+    //初始形状
+    rest_positions: Vec<Point3<f32>>,
+    //初始形状相对质心的偏移，质心随质点质量(如固定质点)变化，质量变化时重新计算
+    rest_offsets: Vec<Vector3<f32>>,
+    //计算rest_offsets时各质点的w
+    rest_ws: Vec<f32>,
+}
+
+impl ShapeMatchingConstraint {
+    pub fn new(p_indexes: Vec<usize>, stiffness_inv: f32) -> ShapeMatchingConstraint {
+        return ShapeMatchingConstraint {
+            p_indexes: p_indexes,
+            stiffness_inv: stiffness_inv,
+            rest_positions: vec![],
+            rest_offsets: vec![],
+            rest_ws: vec![],
+        };
+    }
+
+    fn mass_center<F>(&self, particles: &[ParticleData], position: F) -> Point3<f32>
+    where
+        F: Fn(usize) -> Point3<f32>,
+    {
+        let mut sum = Vector3::zeros();
+        let mut total = 0.0;
+        for (index, p_index) in self.p_indexes.iter().enumerate() {
+            let m = 1.0 / particles[*p_index].w.max(PINNED_W);
+            sum += position(index).coords * m;
+            total += m;
+        }
+        return Point3::from(sum / total);
+    }
+
+    //质点质量变化后重新计算初始形状相对质心的偏移
+    fn update_rest_offsets(&mut self, particles: &[ParticleData]) {
+        let is_changed = self.rest_ws.len() != self.p_indexes.len()
+            || self
+                .p_indexes
+                .iter()
+                .zip(&self.rest_ws)
+                .any(|(p_index, w)| particles[*p_index].w != *w);
+        if !is_changed {
+            return;
+        }
+        let center = self.mass_center(particles, |i| self.rest_positions[i]);
+        self.rest_offsets = self.rest_positions.iter().map(|p| p - center).collect();
+        self.rest_ws = self.p_indexes.iter().map(|i| particles[*i].w).collect();
+    }
+
+    //通过极分解取出 A = sum(m_i * (x_i - c) * q_i^T) 的旋转部分
+    fn goal_transform(&self, particles: &[ParticleData]) -> (Point3<f32>, Rotation3<f32>) {
+        let center = self.mass_center(particles, |i| particles[self.p_indexes[i]].predict_position);
+        let mut a = Matrix3::zeros();
+        for (p_index, q) in self.p_indexes.iter().zip(&self.rest_offsets) {
+            let p = &particles[*p_index];
+            let m = 1.0 / p.w.max(PINNED_W);
+            a += (p.predict_position - center) * q.transpose() * m;
+        }
+        return (center, Rotation3::from_matrix(&a));
+    }
+
+    //固定质点不计入约束值
+    fn value_at(
+        &self,
+        particles: &[ParticleData],
+        center: &Point3<f32>,
+        rotation: &Rotation3<f32>,
+    ) -> f32 {
+        let mut sum = 0.0;
+        for (p_index, q) in self.p_indexes.iter().zip(&self.rest_offsets) {
+            let p = &particles[*p_index];
+            if p.w != 0.0 {
+                sum += (p.predict_position - (center + rotation * q)).norm_squared() / p.w;
+            }
+        }
+        return sum.sqrt();
+    }
+
+    //一次极分解同时求出约束值与梯度，质心与旋转视为常量，dC/dx_i = m_i * (x_i - g_i) / C
+    fn evaluate(&self, particles: &[ParticleData], out_grads: &mut [Vector3<f32>]) -> f32 {
+        let (center, rotation) = self.goal_transform(particles);
+        let c = self.value_at(particles, &center, &rotation);
+        for (index, (p_index, q)) in self.p_indexes.iter().zip(&self.rest_offsets).enumerate() {
+            let p = &particles[*p_index];
+            out_grads[index] = if p.w == 0.0 || c < EPS {
+                Vector3::zeros()
+            } else {
+                (p.predict_position - (center + rotation * q)) / (p.w * c)
+            };
+        }
+        return c;
+    }
+}
+
+impl Constraint for ShapeMatchingConstraint {
+    fn on_initialize(&mut self, particles_data: &[ParticleData]) {
+        self.rest_positions = self
+            .p_indexes
+            .iter()
+            .map(|i| particles_data[*i].predict_position)
+            .collect();
+        self.rest_ws.clear();
+        self.update_rest_offsets(particles_data);
+    }
+    fn get_stiffness_inv(&self) -> f32 {
+        return self.stiffness_inv;
+    }
+    fn get_value(&self, particles: &[ParticleData]) -> f32 {
+        let (center, rotation) = self.goal_transform(particles);
+        return self.value_at(particles, &center, &rotation);
+    }
+    fn get_grad(&self, particles: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        self.evaluate(particles, out_grads);
+    }
+    fn get_pindexes(&self) -> &[usize] {
+        return &self.p_indexes;
+    }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
+        return self.p_indexes.len();
+    }
+}
+
+impl ConstraintsSolver for ConstraintsData<ShapeMatchingConstraint> {
+    fn calculate_cache(&mut self, particles_data: &mut [ParticleData], dt: f32) {
+        ConstraintsData::calculate_cache(self, particles_data, dt);
+        for cs in self.defines.iter_mut() {
+            cs.update_rest_offsets(particles_data);
+        }
+    }
+
+    //梯度在calculate_d_lambdas中与约束值一同计算
+    fn calculate_constraints_gradients(&mut self, _: &mut [ParticleData]) {}
+
+    fn calculate_d_lambdas(&mut self, particles_data: &mut [ParticleData]) {
+        for (c_index, cs) in self.defines.iter().enumerate() {
+            let c_data = &self.datas[c_index];
+            let offset = c_data.grad_offset;
+            let grads = &mut self.grads[offset..offset + cs.p_indexes.len()];
+            let c_value = cs.evaluate(particles_data, grads);
+            let d_lambda = damped_d_lambda(c_data, c_value, grads, &cs.p_indexes, particles_data);
+            self.datas[c_index].d_lambda = d_lambda;
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use nalgebra::Vector3;
+
+use crate::constraints::{constraint::Constraint, particle::ParticleData};
+
+//四面体体积约束，rest为初始化时的有向体积
+#[repr(C)]
+#[derive(Debug)]
+pub struct TetVolumeConstraint {
+    p_indexes: [usize; 4],
+    stiffness_inv: f32,
+    rest: f32,
+}
+
+impl TetVolumeConstraint {
+    pub fn new(p_indexes: [usize; 4], stiffness_inv: f32) -> TetVolumeConstraint {
+        return TetVolumeConstraint {
+            p_indexes: p_indexes,
+            stiffness_inv: stiffness_inv,
+            rest: 0.0,
+        };
+    }
+
+    fn edges(&self, particles: &[ParticleData]) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>) {
+        let p0 = &particles[self.p_indexes[0]].predict_position;
+        let p1 = &particles[self.p_indexes[1]].predict_position;
+        let p2 = &particles[self.p_indexes[2]].predict_position;
+        let p3 = &particles[self.p_indexes[3]].predict_position;
+        return (p1 - p0, p2 - p0, p3 - p0);
+    }
+
+    fn volume(&self, particles: &[ParticleData]) -> f32 {
+        let (e1, e2, e3) = self.edges(particles);
+        return e1.dot(&e2.cross(&e3)) / 6.0;
+    }
+}
+
+impl Constraint for TetVolumeConstraint {
+    fn on_initialize(&mut self, particles_data: &[ParticleData]) {
+        self.rest = self.volume(particles_data);
+    }
+    fn get_stiffness_inv(&self) -> f32 {
+        return self.stiffness_inv;
+    }
+    fn get_value(&self, particles: &[ParticleData]) -> f32 {
+        return self.volume(particles) - self.rest;
+    }
+    fn get_grad(&self, particles: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        let (e1, e2, e3) = self.edges(particles);
+        out_grads[1] = e2.cross(&e3) / 6.0;
+        out_grads[2] = e3.cross(&e1) / 6.0;
+        out_grads[3] = e1.cross(&e2) / 6.0;
+        out_grads[0] = -(out_grads[1] + out_grads[2] + out_grads[3]);
+    }
+    fn get_pindexes(&self) -> &[usize] {
+        return &self.p_indexes;
+    }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
+        return 4;
+    }
+}
@@ -0,0 +1,117 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use nalgebra::{distance, Vector3};
+
+use crate::constraints::{
+    constraint::{Constraint, LambdaBound},
+    particle::ParticleData,
+};
+
+//长程连接约束(Kim et al. 2012)，质点到锚点的距离只允许小于等于rest
+pub struct TetherConstraint {
+    //[锚点, 质点]
+    p_indexes: [usize; 2],
+    stiffness_inv: f32,
+    rest: f32,
+}
+
+impl TetherConstraint {
+    pub fn new(anchor: usize, particle: usize, stiffness_inv: f32, rest: f32) -> TetherConstraint {
+        return TetherConstraint {
+            p_indexes: [anchor, particle],
+            stiffness_inv: stiffness_inv,
+            rest: rest,
+        };
+    }
+
+    pub fn rest(&self) -> f32 {
+        return self.rest;
+    }
+
+    //以所有锚点为源点，沿距离约束构成的边(静止长度为边长)求测地距离，
+    //每个可到达的自由质点连接到最近的锚点
+    pub fn generate(
+        particles_data: &[ParticleData],
+        anchors: &[usize],
+        edges: &[(usize, usize, f32)],
+        stiffness_inv: f32,
+    ) -> Vec<TetherConstraint> {
+        let count = particles_data.len();
+        let mut adjacency = vec![vec![]; count];
+        for (p1, p2, rest) in edges.iter().filter(|e| e.0 < count && e.1 < count) {
+            adjacency[*p1].push((*p2, *rest));
+            adjacency[*p2].push((*p1, *rest));
+        }
+        let mut distances = vec![f32::INFINITY; count];
+        let mut nearest = vec![usize::MAX; count];
+        //非负f32的位表示与数值大小顺序一致
+        let mut heap = BinaryHeap::new();
+        for anchor in anchors.iter().filter(|a| **a < count) {
+            distances[*anchor] = 0.0;
+            nearest[*anchor] = *anchor;
+            heap.push(Reverse((0.0f32.to_bits(), *anchor)));
+        }
+        while let Some(Reverse((bits, index))) = heap.pop() {
+            let d = f32::from_bits(bits);
+            if d > distances[index] {
+                continue;
+            }
+            for (next, rest) in adjacency[index].iter() {
+                let next_d = d + rest.max(0.0);
+                if next_d < distances[*next] {
+                    distances[*next] = next_d;
+                    nearest[*next] = nearest[index];
+                    heap.push(Reverse((next_d.to_bits(), *next)));
+                }
+            }
+        }
+        let mut tethers = vec![];
+        for (index, p) in particles_data.iter().enumerate() {
+            //锚点本身的w为0
+            if p.w == 0.0 || nearest[index] == usize::MAX {
+                continue;
+            }
+            tethers.push(TetherConstraint::new(
+                nearest[index],
+                index,
+                stiffness_inv,
+                distances[index],
+            ));
+        }
+        return tethers;
+    }
+}
+
+impl Constraint for TetherConstraint {
+    fn on_initialize(&mut self, _: &[ParticleData]) {}
+    fn get_stiffness_inv(&self) -> f32 {
+        return self.stiffness_inv;
+    }
+    fn get_value(&self, particles: &[ParticleData]) -> f32 {
+        let p0 = &particles[self.p_indexes[0]].predict_position;
+        let p1 = &particles[self.p_indexes[1]].predict_position;
+        return distance(p0, p1) - self.rest;
+    }
+    fn get_grad(&self, particles: &[ParticleData], out_grads: &mut [Vector3<f32>]) {
+        let p0 = &particles[self.p_indexes[0]].predict_position;
+        let p1 = &particles[self.p_indexes[1]].predict_position;
+        let n = (p0 - p1)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vector3::zeros());
+        out_grads[0] = n;
+        out_grads[1] = -n;
+    }
+    //不超过rest时约束不起作用，C <= 0
+    fn get_lambda_bound(&self, _: &[ParticleData]) -> LambdaBound {
+        return LambdaBound::NonPositive;
+    }
+    fn get_pindexes(&self) -> &[usize] {
+        return &self.p_indexes;
+    }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_constraint_particle_count(&self) -> usize {
+        return 2;
+    }
+}
@@ -3,7 +3,7 @@ use std::f32::NAN;
 use nalgebra::{inf, Vector3};
 
 use crate::constraints::{
-    constraint::{Constraint, ConstraintData, ConstraintsData},
+    constraint::{BreakThreshold, Constraint, ConstraintData, ConstraintsData},
     particle::ParticlesDataBuilder,
     solver::ConstraintsSolver,
 };
@@ -14,6 +14,8 @@ pub struct TriangleBendConstraint {
     pub p_indexes: [usize; 4],
     pub stiffness_inv: f32,
     rest: f32,
+    break_threshold: BreakThreshold,
+    damping: f32,
 }
 
 impl TriangleBendConstraint {
@@ -22,8 +24,32 @@ impl TriangleBendConstraint {
             p_indexes: p_indexes,
             stiffness_inv: stiffness_inv,
             rest: 0.0,
+            break_threshold: BreakThreshold::NONE,
+            damping: 0.0,
         };
     }
+    //使用给定的静止二面角，不再由on_initialize计算
+    pub fn with_rest(
+        p_indexes: [usize; 4],
+        stiffness_inv: f32,
+        rest: f32,
+    ) -> TriangleBendConstraint {
+        return TriangleBendConstraint {
+            p_indexes: p_indexes,
+            stiffness_inv: stiffness_inv,
+            rest: rest,
+            break_threshold: BreakThreshold::NONE,
+            damping: 0.0,
+        };
+    }
+    pub fn set_break_threshold(&mut self, threshold: BreakThreshold) {
+        self.break_threshold = threshold;
+    }
+
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping;
+    }
+
     fn get_rest(&self, particles_data: &[crate::constraints::particle::ParticleData]) -> f32 {
         let (n1, n2) = self.get_n1_n2(particles_data);
         let n1_dot_n2 = n1.dot(&n2).max(-1.0).min(1.0);
@@ -87,19 +113,29 @@ impl TriangleBendConstraint {
     ) -> f32 {
         let c_value = self.get_value(particles_data);
         let a = c_data.alpha;
+        let gamma = c_data.gamma;
         let lambda = c_data.lambda;
         let (q1, q2, q3, q4, d) = self.get_q_and_d(particles_data);
         let qs = [q1, q2, q3, q4];
         let one_minus_d2 = 1.0 - d * d;
         let mut sum_gwg = 0.;
+        let mut sum_qdx = 0.;
         for (g_index, p_index) in self.get_pindexes().iter().enumerate() {
             let q = &qs[g_index];
             let p_data = &particles_data[*p_index];
             sum_gwg += p_data.w * q.dot(q);
+            sum_qdx += q.dot(&(p_data.predict_position - p_data.position));
         }
+        //梯度为q / sqrt(1 - d^2)，分子分母同乘(1 - d^2)，阻尼项grad·dx = sum_qdx / sqrt(1 - d^2)
+        let sqrt_one_minus_d2 = one_minus_d2.sqrt();
+        let damping = if sqrt_one_minus_d2 > 0.0 {
+            gamma * sum_qdx / sqrt_one_minus_d2
+        } else {
+            0.0
+        };
 
-        let d_lambda_1 = -(a * lambda + c_value) * one_minus_d2;
-        let d_lambda_2 = sum_gwg + a * one_minus_d2;
+        let d_lambda_1 = -(a * lambda + c_value + damping) * one_minus_d2;
+        let d_lambda_2 = (1.0 + gamma) * sum_gwg + a * one_minus_d2;
 
         if d_lambda_2 == 0.0 {
             return 0.0;
@@ -136,8 +172,17 @@ impl Constraint for TriangleBendConstraint {
     fn get_pindexes(&self) -> &[usize] {
         return &self.p_indexes;
     }
+    fn get_pindexes_mut(&mut self) -> &mut [usize] {
+        return &mut self.p_indexes;
+    }
+    fn get_damping(&self) -> f32 {
+        return self.damping;
+    }
+    fn get_break_threshold(&self) -> BreakThreshold {
+        return self.break_threshold;
+    }
 
-    fn get_constraint_particle_count() -> usize {
+    fn get_constraint_particle_count(&self) -> usize {
         return 4;
     }
 }
@@ -168,6 +213,9 @@ fn test() {
     let particles_data = ParticlesDataBuilder {
         positions: positions,
         masses: masses,
+        radii: vec![],
+        default_radius: 0.,
+        dampings: vec![],
     }
     .build()
     .unwrap();
@@ -176,6 +224,8 @@ fn test() {
         p_indexes: [0, 2, 1, 3],
         stiffness_inv: 0.,
         rest: 3.1415926,
+        break_threshold: BreakThreshold::NONE,
+        damping: 0.0,
     };
 
     println!("c_value = {}", cs.get_value(&particles_data));
@@ -186,3 +236,56 @@ fn test() {
     cs.get_grad(&particles_data, &mut grads);
     println!("{:?}", grads);
 }
+
+#[test]
+fn test_damping() {
+    let positions = vec![
+        point![0.0, 0.0, 0.0],
+        point![1.0, -0.5, 0.0],
+        point![1.0, 0.0, 1.0],
+        point![0.0, -0.5, 1.0],
+    ];
+    let mut particles_data = ParticlesDataBuilder {
+        positions: positions,
+        masses: vec![1.0, 2.0, 1.0, 0.5],
+        radii: vec![],
+        default_radius: 0.,
+        dampings: vec![],
+    }
+    .build()
+    .unwrap();
+    let mut cs = TriangleBendConstraint::new([0, 2, 1, 3], 0.0001);
+    cs.on_initialize(&particles_data);
+    cs.set_damping(100.0);
+    //预测位置偏离上一位置，约束值与阻尼项都不为0
+    particles_data[1].predict_position += Vector3::new(0.0, -0.1, 0.05);
+    particles_data[3].predict_position += Vector3::new(0.1, 0.2, 0.0);
+    let dt = 0.01;
+    let mut c_data = ConstraintData {
+        alpha: cs.stiffness_inv / (dt * dt),
+        gamma: cs.stiffness_inv * cs.get_damping() / dt,
+        lambda: 0.01,
+        ..Default::default()
+    };
+    let d_lambda = cs.calculate_d_lambdas(&mut c_data, &mut particles_data);
+
+    //与使用完整梯度的XPBD公式一致
+    let mut grads = [Vector3::zeros(); 4];
+    cs.get_grad(&particles_data, &mut grads);
+    let mut sum_gwg = 0.;
+    let mut sum_gdx = 0.;
+    for (grad, p_index) in grads.iter().zip(cs.p_indexes.iter()) {
+        let p_data = &particles_data[*p_index];
+        sum_gwg += p_data.w * grad.dot(grad);
+        sum_gdx += grad.dot(&(p_data.predict_position - p_data.position));
+    }
+    let expected =
+        -(c_data.alpha * c_data.lambda + cs.get_value(&particles_data) + c_data.gamma * sum_gdx)
+            / ((1.0 + c_data.gamma) * sum_gwg + c_data.alpha);
+    assert!(
+        (d_lambda - expected).abs() < 1e-4 * expected.abs(),
+        "{} {}",
+        d_lambda,
+        expected
+    );
+}
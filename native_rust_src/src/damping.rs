@@ -0,0 +1,128 @@
+use nalgebra::{Matrix3, Point3, Vector3};
+
+use crate::constraints::{particle::ParticleData, solver::ConstraintsSolver};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DampingSettings {
+    //全局线性阻尼，每秒衰减的速度比例，与质点自身的damping叠加
+    pub linear: f32,
+    //每秒向整体刚体运动(平移+旋转)靠拢的比例，与linear同样按dt换算，0为不启用
+    pub rigid: f32,
+}
+
+//刚体阻尼按约束连通的质点分组，每组单独计算整体运动
+#[derive(Default)]
+pub struct Damping {
+    pub settings: DampingSettings,
+    groups: Vec<Vec<usize>>,
+}
+
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    return index;
+}
+
+impl Damping {
+    pub fn new(settings: DampingSettings) -> Damping {
+        return Damping {
+            settings: settings,
+            groups: vec![],
+        };
+    }
+
+    //约束拓扑变化后调用，links为约束以外相互连接的质点对，例如杆的分段
+    pub fn update_groups<T: ConstraintsSolver>(
+        &mut self,
+        particle_count: usize,
+        constraints: &T,
+        links: impl Iterator<Item = [usize; 2]>,
+    ) {
+        let mut parents: Vec<usize> = (0..particle_count).collect();
+        let mut union = |indexes: &[usize]| {
+            if let Some((first, others)) = indexes.split_first() {
+                let root = find_root(&mut parents, *first);
+                for index in others {
+                    let other = find_root(&mut parents, *index);
+                    parents[other] = root;
+                }
+            }
+        };
+        constraints.for_each_pindexes(&mut union);
+        for link in links {
+            union(&link);
+        }
+        let mut group_of_root = vec![usize::MAX; particle_count];
+        self.groups.clear();
+        for index in 0..particle_count {
+            let root = find_root(&mut parents, index);
+            if group_of_root[root] == usize::MAX {
+                group_of_root[root] = self.groups.len();
+                self.groups.push(vec![]);
+            }
+            self.groups[group_of_root[root]].push(index);
+        }
+        //单个质点的刚体运动即为其自身运动，不需要处理
+        self.groups.retain(|group| group.len() > 1);
+    }
+
+    pub fn apply(&self, particles: &mut [ParticleData], dt: f32) {
+        let k = (self.settings.rigid * dt).min(1.0);
+        if k > 0.0 {
+            for group in self.groups.iter() {
+                Self::damp_rigid_motion(particles, group, k);
+            }
+        }
+        for p in particles.iter_mut() {
+            let damping = self.settings.linear + p.damping;
+            if damping > 0.0 {
+                p.velocity *= (1.0 - damping * dt).max(0.0);
+            }
+        }
+    }
+
+    //Müller et al. Position Based Dynamics 3.5
+    fn damp_rigid_motion(particles: &mut [ParticleData], group: &[usize], k: f32) {
+        let mut mass = 0.0;
+        let mut center = Vector3::zeros();
+        let mut velocity = Vector3::zeros();
+        for p in group.iter().map(|i| &particles[*i]).filter(|p| p.w > 0.0) {
+            let m = 1.0 / p.w;
+            mass += m;
+            center += p.position.coords * m;
+            velocity += p.velocity * m;
+        }
+        if mass == 0.0 {
+            return;
+        }
+        let center = Point3::from(center / mass);
+        velocity /= mass;
+
+        let mut angular_momentum = Vector3::zeros();
+        let mut inertia = Matrix3::zeros();
+        for p in group.iter().map(|i| &particles[*i]).filter(|p| p.w > 0.0) {
+            let m = 1.0 / p.w;
+            let r = p.position - center;
+            angular_momentum += r.cross(&(p.velocity * m));
+            let r_cross = r.cross_matrix();
+            inertia += r_cross * r_cross.transpose() * m;
+        }
+        let angular_velocity = match inertia.try_inverse() {
+            Some(inertia_inv) => inertia_inv * angular_momentum,
+            None => Vector3::zeros(),
+        };
+
+        for index in group.iter() {
+            let p = &mut particles[*index];
+            if p.w == 0.0 {
+                continue;
+            }
+            let r = p.position - center;
+            let dv = velocity + angular_velocity.cross(&r) - p.velocity;
+            p.velocity += dv * k;
+        }
+    }
+}
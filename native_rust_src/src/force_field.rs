@@ -0,0 +1,187 @@
+use nalgebra::{distance, Point3, Vector3};
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForceFieldKind {
+    //沿direction方向
+    Directional = 0,
+    //指向position，strength为负时向外排斥
+    Radial = 1,
+    //绕过position、方向为direction的轴旋转
+    Vortex = 2,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForceFieldFalloff {
+    None = 0,
+    //在radius处线性衰减到0
+    Linear = 1,
+    //1 / (1 + d^2)
+    InverseSquare = 2,
+}
+
+impl ForceFieldKind {
+    //C#传入的值不在枚举范围内时返回None
+    pub fn from_u32(value: u32) -> Option<ForceFieldKind> {
+        return match value {
+            0 => Some(ForceFieldKind::Directional),
+            1 => Some(ForceFieldKind::Radial),
+            2 => Some(ForceFieldKind::Vortex),
+            _ => None,
+        };
+    }
+}
+
+impl ForceFieldFalloff {
+    //C#传入的值不在枚举范围内时返回None
+    pub fn from_u32(value: u32) -> Option<ForceFieldFalloff> {
+        return match value {
+            0 => Some(ForceFieldFalloff::None),
+            1 => Some(ForceFieldFalloff::Linear),
+            2 => Some(ForceFieldFalloff::InverseSquare),
+            _ => None,
+        };
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ForceField {
+    //ForceFieldKind与ForceFieldFalloff的原始值，由C#传入，通过kind()与falloff()校验后读取
+    kind: u32,
+    falloff: u32,
+    pub position: Point3<f32>,
+    pub direction: Vector3<f32>,
+    pub strength: f32,
+    //以position为中心的作用范围，小于等于0时不限范围
+    pub radius: f32,
+    //为true时作为加速度施加，与质点质量无关
+    pub is_acceleration: bool,
+}
+
+impl ForceField {
+    pub fn new(
+        kind: ForceFieldKind,
+        falloff: ForceFieldFalloff,
+        position: Point3<f32>,
+        direction: Vector3<f32>,
+        strength: f32,
+        radius: f32,
+        is_acceleration: bool,
+    ) -> ForceField {
+        return ForceField {
+            kind: kind as u32,
+            falloff: falloff as u32,
+            position: position,
+            direction: direction,
+            strength: strength,
+            radius: radius,
+            is_acceleration: is_acceleration,
+        };
+    }
+
+    //kind不合法时返回None
+    pub fn kind(&self) -> Option<ForceFieldKind> {
+        return ForceFieldKind::from_u32(self.kind);
+    }
+
+    //falloff不合法时返回None
+    pub fn falloff(&self) -> Option<ForceFieldFalloff> {
+        return ForceFieldFalloff::from_u32(self.falloff);
+    }
+
+    pub fn is_valid(&self) -> bool {
+        return self.kind().is_some() && self.falloff().is_some();
+    }
+
+    //kind或falloff不合法时返回0
+    pub fn evaluate(&self, p: &Point3<f32>) -> Vector3<f32> {
+        let (kind, falloff) = match (self.kind(), self.falloff()) {
+            (Some(kind), Some(falloff)) => (kind, falloff),
+            _ => return Vector3::zeros(),
+        };
+        let d = distance(&self.position, p);
+        if self.radius > 0.0 && d > self.radius {
+            return Vector3::zeros();
+        }
+        let scale = match falloff {
+            ForceFieldFalloff::None => 1.0,
+            ForceFieldFalloff::Linear if self.radius > 0.0 => 1.0 - d / self.radius,
+            ForceFieldFalloff::Linear => 1.0,
+            ForceFieldFalloff::InverseSquare => 1.0 / (1.0 + d * d),
+        };
+        let direction = match kind {
+            ForceFieldKind::Directional => self.direction.try_normalize(f32::EPSILON),
+            ForceFieldKind::Radial => (self.position - p).try_normalize(f32::EPSILON),
+            ForceFieldKind::Vortex => self
+                .direction
+                .cross(&(p - self.position))
+                .try_normalize(f32::EPSILON),
+        };
+        return match direction {
+            Some(direction) => direction * (self.strength * scale),
+            None => Vector3::zeros(),
+        };
+    }
+}
+
+//id单调递增，移除后不会复用，过期的id不会误改之后加入的力场
+#[derive(Default)]
+pub struct ForceFields {
+    //按id升序排列
+    fields: Vec<(usize, ForceField)>,
+    next_id: usize,
+}
+
+impl ForceFields {
+    pub fn add(&mut self, field: ForceField) -> usize {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.fields.push((id, field));
+        return id;
+    }
+
+    fn index_of(&self, id: usize) -> Option<usize> {
+        return self.fields.binary_search_by_key(&id, |(id, _)| *id).ok();
+    }
+
+    pub fn set(&mut self, id: usize, field: ForceField) -> bool {
+        return match self.index_of(id) {
+            Some(index) => {
+                self.fields[index].1 = field;
+                true
+            }
+            None => false,
+        };
+    }
+
+    pub fn remove(&mut self, id: usize) -> bool {
+        return match self.index_of(id) {
+            Some(index) => {
+                self.fields.remove(index);
+                true
+            }
+            None => false,
+        };
+    }
+
+    //next_id不重置，清空前的id依然无效
+    pub fn clear(&mut self) {
+        self.fields.clear();
+    }
+
+    //返回p处的(力, 加速度)
+    pub fn evaluate(&self, p: &Point3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+        let mut force = Vector3::zeros();
+        let mut acc = Vector3::zeros();
+        for (_, field) in self.fields.iter() {
+            if field.is_acceleration {
+                acc += field.evaluate(p);
+            } else {
+                force += field.evaluate(p);
+            }
+        }
+        return (force, acc);
+    }
+}
@@ -11,8 +11,16 @@ mod collision;
 mod collision_constraint;
 mod constraints;
 mod constraints_impl;
+mod damping;
 mod debug;
+mod force_field;
+mod rigid_body;
+mod rod;
+mod self_collision;
+mod tearing;
+#[cfg(test)]
 mod test;
+mod wind;
 mod xpbd;
 
 #[test]
@@ -27,8 +35,10 @@ fn test2() {
         iterate_count: 4,
         bounciness: 1.0,
         dynamic_friction_factor: 1.0,
+        ..Default::default()
     }
-    .build();
+    .build()
+    .unwrap();
 
     xphd.add_acceleration_field(vector![0., -9.8, 0.]);
     xphd.attach(1, point![1., 1., 0.]);
@@ -68,9 +78,10 @@ fn test() {
         iterate_count: 4,
         bounciness: 1.0,
         dynamic_friction_factor: 1.0,
+        ..Default::default()
     };
 
-    let mut xpbd = builder.build();
+    let mut xpbd = builder.build().unwrap();
     xpbd.add_field_force(vector![0., -9.8, 0.]);
     for i in 0..100 {
         // println!("step begin {}",i);
@@ -0,0 +1,456 @@
+use nalgebra::{Point3, Quaternion, UnitQuaternion, Vector3};
+
+use crate::{
+    collision::{
+        collider::Pose,
+        intersect::GetClosestSurfacePoint,
+        shapes::{OrientedBox, ShapesManager, Sphere},
+    },
+    constraints::particle::ParticleData,
+    tearing::is_on_split_side,
+};
+
+const EPS: f32 = 0.000001;
+//刚体间接触中表示静态碰撞体
+const NO_BODY: usize = usize::MAX;
+
+#[derive(Clone, Copy, Debug)]
+pub enum RigidBodyShape {
+    Sphere { radius: f32 },
+    Box { half_extents: Vector3<f32> },
+}
+
+//刚体，w与inv_inertia为0时视为运动学刚体，只受外部设置的pose影响
+pub struct RigidBody {
+    pub position: Point3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    pub velocity: Vector3<f32>,
+    pub angular_velocity: Vector3<f32>,
+    pub w: f32, // 1 / mass
+    //局部空间下的惯性张量的逆(对角线)
+    pub inv_inertia: Vector3<f32>,
+    pub shape: RigidBodyShape,
+    prev_position: Point3<f32>,
+    prev_rotation: UnitQuaternion<f32>,
+}
+
+impl RigidBody {
+    pub fn new(pose: Pose, mass: f32, shape: RigidBodyShape) -> RigidBody {
+        let inertia = match shape {
+            RigidBodyShape::Sphere { radius } => {
+                let i = 0.4 * mass * radius * radius;
+                vector![i, i, i]
+            }
+            RigidBodyShape::Box { half_extents: h } => {
+                let (x2, y2, z2) = (h.x * h.x, h.y * h.y, h.z * h.z);
+                vector![y2 + z2, x2 + z2, x2 + y2] * (mass / 3.0)
+            }
+        };
+        let inv = |v: f32| if v > 0.0 { 1.0 / v } else { 0.0 };
+        return RigidBody {
+            position: pose.position,
+            rotation: pose.rotation,
+            velocity: Vector3::zeros(),
+            angular_velocity: Vector3::zeros(),
+            w: inv(mass),
+            inv_inertia: inertia.map(inv),
+            shape: shape,
+            prev_position: pose.position,
+            prev_rotation: pose.rotation,
+        };
+    }
+
+    pub fn pose(&self) -> Pose {
+        return Pose {
+            position: self.position,
+            rotation: self.rotation,
+        };
+    }
+
+    fn apply_inv_inertia(&self, v: &Vector3<f32>) -> Vector3<f32> {
+        let local = self.rotation.inverse_transform_vector(v);
+        return self.rotation * local.component_mul(&self.inv_inertia);
+    }
+
+    //在世界空间偏移r处沿n方向施加位置修正时的广义质量倒数
+    fn generalized_w(&self, r: &Vector3<f32>, n: &Vector3<f32>) -> f32 {
+        let rn = r.cross(n);
+        return self.w + rn.dot(&self.apply_inv_inertia(&rn));
+    }
+
+    fn apply_correction(&mut self, r: &Vector3<f32>, p: &Vector3<f32>) {
+        self.position += p * self.w;
+        let omega = self.apply_inv_inertia(&r.cross(p));
+        self.rotate(&omega);
+    }
+
+    fn rotate(&mut self, omega: &Vector3<f32>) {
+        let q = self.rotation.quaternion();
+        let dq = Quaternion::from_parts(0.0, *omega) * q * 0.5;
+        self.rotation = UnitQuaternion::new_normalize(q + dq);
+    }
+
+    //瞬移到pose，速度清零，不会因位移产生速度
+    pub fn set_pose(&mut self, pose: Pose) {
+        self.position = pose.position;
+        self.rotation = pose.rotation;
+        self.prev_position = pose.position;
+        self.prev_rotation = pose.rotation;
+        self.velocity = Vector3::zeros();
+        self.angular_velocity = Vector3::zeros();
+    }
+
+    fn predict(&mut self, dt: f32, field_force: &Vector3<f32>, acc: &Vector3<f32>) {
+        self.prev_position = self.position;
+        self.prev_rotation = self.rotation;
+        //运动学刚体不受力，按外部设置的速度运动
+        if self.w != 0.0 {
+            self.velocity += dt * (field_force * self.w + acc);
+        }
+        self.position += dt * self.velocity;
+        self.rotate(&(self.angular_velocity * dt));
+    }
+
+    fn update_velocity(&mut self, dt: f32) {
+        //运动学刚体保持外部设置的速度
+        if self.w == 0.0 {
+            return;
+        }
+        self.velocity = (self.position - self.prev_position) / dt;
+        let dq = self.rotation.quaternion() * self.prev_rotation.quaternion().conjugate();
+        let omega = dq.imag() * (2.0 / dt);
+        self.angular_velocity = if dq.w >= 0.0 { omega } else { -omega };
+    }
+
+    //包围球半径，用于刚体间的粗略剔除
+    fn bounding_radius(&self) -> f32 {
+        return match self.shape {
+            RigidBodyShape::Sphere { radius } => radius,
+            RigidBodyShape::Box { half_extents } => half_extents.norm(),
+        };
+    }
+
+    //与碰撞体或其它刚体求交时使用的表面采样点(局部空间)。
+    //盒子取8个顶点；球体取世界空间中的固定方向，静止在平面上时最低点恰好被采样
+    fn sample_points(&self, out: &mut Vec<Point3<f32>>) {
+        out.clear();
+        match self.shape {
+            RigidBodyShape::Sphere { radius } => {
+                //3x3x3网格中除中心外的26个方向
+                for i in 0..27 {
+                    let d = vector![
+                        (i % 3) as f32 - 1.0,
+                        (i / 3 % 3) as f32 - 1.0,
+                        (i / 9) as f32 - 1.0
+                    ];
+                    if let Some(d) = d.try_normalize(EPS) {
+                        let local = self.rotation.inverse_transform_vector(&d) * radius;
+                        out.push(Point3::from(local));
+                    }
+                }
+            }
+            RigidBodyShape::Box { half_extents: h } => {
+                for i in 0..8 {
+                    let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+                    out.push(point![h.x * sign(1), h.y * sign(2), h.z * sign(4)]);
+                }
+            }
+        }
+    }
+
+    //margin为质点半径，形状按margin膨胀后求交，使表面附近半径范围内的质点也能产生接触。
+    //返回的接触点仍位于未膨胀的表面上
+    fn get_contact(&self, p: &Point3<f32>, margin: f32) -> Option<(Point3<f32>, Vector3<f32>)> {
+        let local_p = Point3::from(self.rotation.inverse_transform_vector(&(p - self.position)));
+        let info = match self.shape {
+            RigidBodyShape::Sphere { radius } => Sphere {
+                center: Point3::origin(),
+                radius: radius + margin,
+            }
+            .get_closest_surface_point(&local_p),
+            RigidBodyShape::Box { half_extents } => OrientedBox {
+                center: Point3::origin(),
+                rotation: UnitQuaternion::identity(),
+                half_extents: half_extents.add_scalar(margin),
+            }
+            .get_closest_surface_point(&local_p),
+        };
+        if info.contacted {
+            let contact_position = info.contact_position - info.contact_normal * margin;
+            return Some((contact_position, info.contact_normal));
+        }
+        return None;
+    }
+}
+
+//质点与刚体上一点之间的连接约束
+pub struct RigidAttachment {
+    pub particle: usize,
+    pub body: usize,
+    pub local_point: Point3<f32>,
+    pub stiffness_inv: f32,
+    lambda: f32,
+}
+
+impl RigidAttachment {
+    pub fn new(
+        particle: usize,
+        body: usize,
+        local_point: Point3<f32>,
+        stiffness_inv: f32,
+    ) -> RigidAttachment {
+        return RigidAttachment {
+            particle: particle,
+            body: body,
+            local_point: local_point,
+            stiffness_inv: stiffness_inv,
+            lambda: 0.0,
+        };
+    }
+}
+
+//质点与刚体的碰撞，接触点与法线记录在刚体局部空间中
+struct RigidContact {
+    particle: usize,
+    body: usize,
+    local_point: Point3<f32>,
+    local_normal: Vector3<f32>,
+    lambda: f32,
+}
+
+//刚体表面采样点与碰撞体或另一刚体的碰撞。
+//other为NO_BODY时other_point与other_normal为碰撞体表面的世界坐标，否则记录在other的局部空间中
+struct BodyContact {
+    body: usize,
+    local_point: Point3<f32>,
+    other: usize,
+    other_point: Point3<f32>,
+    other_normal: Vector3<f32>,
+}
+
+#[derive(Default)]
+pub struct RigidBodies {
+    pub bodies: Vec<RigidBody>,
+    //连接的索引返回给外部使用，移除的连接保留空位，避免其余连接的索引发生变化
+    pub attachments: Vec<Option<RigidAttachment>>,
+    contacts: Vec<RigidContact>,
+    body_contacts: Vec<BodyContact>,
+    samples: Vec<Point3<f32>>,
+}
+
+//同时可变借用两个不同的刚体
+fn two_bodies(bodies: &mut [RigidBody], a: usize, b: usize) -> (&mut RigidBody, &mut RigidBody) {
+    if a < b {
+        let (left, right) = bodies.split_at_mut(b);
+        return (&mut left[a], &mut right[0]);
+    }
+    let (left, right) = bodies.split_at_mut(a);
+    return (&mut right[0], &mut left[b]);
+}
+
+impl RigidBodies {
+    pub fn predict(&mut self, dt: f32, field_force: &Vector3<f32>, acc: &Vector3<f32>) {
+        for b in self.bodies.iter_mut() {
+            b.predict(dt, field_force, acc);
+        }
+    }
+
+    pub fn generate_contacts(
+        &mut self,
+        particles_data: &[ParticleData],
+        colliders: &ShapesManager,
+    ) {
+        self.contacts.clear();
+        self.body_contacts.clear();
+        if self.bodies.is_empty() {
+            return;
+        }
+        self.generate_body_contacts(colliders);
+        for (p_index, p) in particles_data.iter().enumerate() {
+            for (b_index, b) in self.bodies.iter().enumerate() {
+                if let Some((local_point, local_normal)) =
+                    b.get_contact(&p.predict_position, p.radius)
+                {
+                    self.contacts.push(RigidContact {
+                        particle: p_index,
+                        body: b_index,
+                        local_point: local_point,
+                        local_normal: local_normal,
+                        lambda: 0.0,
+                    });
+                }
+            }
+        }
+    }
+
+    //刚体与碰撞体、刚体之间的碰撞，以表面采样点检测
+    fn generate_body_contacts(&mut self, colliders: &ShapesManager) {
+        let samples = &mut self.samples;
+        let body_contacts = &mut self.body_contacts;
+        for (b_index, b) in self.bodies.iter().enumerate() {
+            b.sample_points(samples);
+            for local_point in samples.iter() {
+                let world_point = b.position + b.rotation * local_point.coords;
+                //运动学刚体不受碰撞体影响
+                if b.w != 0.0 {
+                    colliders.for_each_contact(&world_point, |info| {
+                        body_contacts.push(BodyContact {
+                            body: b_index,
+                            local_point: *local_point,
+                            other: NO_BODY,
+                            other_point: info.contact_position,
+                            other_normal: info.contact_normal,
+                        });
+                    });
+                }
+                for (o_index, o) in self.bodies.iter().enumerate() {
+                    if o_index == b_index || (b.w == 0.0 && o.w == 0.0) {
+                        continue;
+                    }
+                    let reach = b.bounding_radius() + o.bounding_radius();
+                    if (b.position - o.position).norm_squared() > reach * reach {
+                        continue;
+                    }
+                    if let Some((other_point, other_normal)) = o.get_contact(&world_point, 0.0) {
+                        body_contacts.push(BodyContact {
+                            body: b_index,
+                            local_point: *local_point,
+                            other: o_index,
+                            other_point: other_point,
+                            other_normal: other_normal,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    //Detailed Rigid Body Simulation with Extended Position Based Dynamics, 3.3.1
+    fn solve_positional(
+        particle: &mut ParticleData,
+        body: &mut RigidBody,
+        world_point: &Point3<f32>,
+        n: &Vector3<f32>,
+        c: f32,
+        alpha: f32,
+        lambda: &mut f32,
+    ) {
+        let r = world_point - body.position;
+        let w = particle.w + body.generalized_w(&r, n);
+        if w + alpha < EPS {
+            return;
+        }
+        let d_lambda = (-c - alpha * *lambda) / (w + alpha);
+        *lambda += d_lambda;
+        let p = n * d_lambda;
+        particle.predict_position += p * particle.w;
+        body.apply_correction(&r, &(-p));
+    }
+
+    //每个子步开始求解前调用一次
+    pub fn begin_solve(&mut self) {
+        for a in self.attachments.iter_mut().flatten() {
+            a.lambda = 0.0;
+        }
+        for contact in self.contacts.iter_mut() {
+            contact.lambda = 0.0;
+        }
+    }
+
+    //一次求解迭代，与质点约束在同一迭代循环中交替求解
+    pub fn solve_iteration(&mut self, particles_data: &mut [ParticleData], dt: f32) {
+        for a in self.attachments.iter_mut().flatten() {
+            let body = &mut self.bodies[a.body];
+            let particle = &mut particles_data[a.particle];
+            let anchor = body.position + body.rotation * a.local_point.coords;
+            let d = particle.predict_position - anchor;
+            let c = d.norm();
+            let n = match d.try_normalize(EPS) {
+                Some(n) => n,
+                None => continue,
+            };
+            let alpha = a.stiffness_inv / (dt * dt);
+            Self::solve_positional(particle, body, &anchor, &n, c, alpha, &mut a.lambda);
+        }
+        for contact in self.contacts.iter_mut() {
+            let body = &mut self.bodies[contact.body];
+            let particle = &mut particles_data[contact.particle];
+            let point = body.position + body.rotation * contact.local_point.coords;
+            let n = body.rotation * contact.local_normal;
+            //单侧约束，只在穿透时推开
+            let c = (particle.predict_position - point).dot(&n) - particle.radius;
+            if c >= 0.0 {
+                continue;
+            }
+            Self::solve_positional(particle, body, &point, &n, c, 0.0, &mut contact.lambda);
+        }
+        for contact in self.body_contacts.iter() {
+            if contact.other == NO_BODY {
+                let body = &mut self.bodies[contact.body];
+                let r = body.rotation * contact.local_point.coords;
+                let n = contact.other_normal;
+                let c = (body.position + r - contact.other_point).dot(&n);
+                let w = body.generalized_w(&r, &n);
+                if c >= 0.0 || w < EPS {
+                    continue;
+                }
+                body.apply_correction(&r, &(n * (-c / w)));
+            } else {
+                let (body, other) = two_bodies(&mut self.bodies, contact.body, contact.other);
+                let r = body.rotation * contact.local_point.coords;
+                let other_r = other.rotation * contact.other_point.coords;
+                let n = other.rotation * contact.other_normal;
+                let c = (body.position + r - other.position - other_r).dot(&n);
+                let w = body.generalized_w(&r, &n) + other.generalized_w(&other_r, &n);
+                if c >= 0.0 || w < EPS {
+                    continue;
+                }
+                let p = n * (-c / w);
+                body.apply_correction(&r, &p);
+                other.apply_correction(&other_r, &(-p));
+            }
+        }
+    }
+
+    //撕裂时刚体上的连接点位于normal正方向一侧的连接改为连接质点to
+    pub fn split_particle(
+        &mut self,
+        particles_data: &[ParticleData],
+        from: usize,
+        to: usize,
+        normal: &Vector3<f32>,
+    ) {
+        let origin = &particles_data[from].predict_position;
+        for a in self
+            .attachments
+            .iter_mut()
+            .flatten()
+            .filter(|a| a.particle == from)
+        {
+            let body = &self.bodies[a.body];
+            let anchor = body.position + body.rotation * a.local_point.coords;
+            if is_on_split_side(origin, std::iter::once(&anchor), normal) {
+                a.particle = to;
+            }
+        }
+    }
+
+    //质点被移除或重新编号后更新连接，连接到被移除质点的连接置空，其余连接的索引不变
+    pub fn remap_particles(&mut self, remap: &[Option<usize>]) {
+        for slot in self.attachments.iter_mut() {
+            if let Some(a) = slot {
+                match remap[a.particle] {
+                    Some(p) => a.particle = p,
+                    None => *slot = None,
+                }
+            }
+        }
+        self.contacts.clear();
+    }
+
+    pub fn update_velocities(&mut self, dt: f32) {
+        for b in self.bodies.iter_mut() {
+            b.update_velocity(dt);
+        }
+    }
+}
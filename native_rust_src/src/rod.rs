@@ -0,0 +1,267 @@
+use std::ops::Range;
+
+use nalgebra::{Quaternion, UnitQuaternion, Vector3};
+
+use crate::{constraints::particle::ParticleData, tearing::is_on_split_side};
+
+const EPS: f32 = 0.000001;
+
+//杆的一段，连接相邻两个质点，朝向的局部z轴为静止时的切线方向
+pub struct RodSegment {
+    pub p_indexes: [usize; 2],
+    pub orientation: UnitQuaternion<f32>,
+    pub angular_velocity: Vector3<f32>,
+    //朝向的质量倒数，为0时朝向固定不动
+    pub wq: f32,
+    pub rest_length: f32,
+    prev_orientation: UnitQuaternion<f32>,
+    //拉伸剪切约束
+    stiffness_inv: f32,
+    lambda: Vector3<f32>,
+}
+
+impl RodSegment {
+    //局部z轴在世界空间中的方向
+    fn d3(&self) -> Vector3<f32> {
+        return self.orientation * Vector3::z();
+    }
+
+    fn rotate(&mut self, dq: &Quaternion<f32>) {
+        self.orientation = UnitQuaternion::new_normalize(self.orientation.quaternion() + dq);
+    }
+}
+
+//相邻两段之间的弯曲扭转约束
+struct BendTwistConstraint {
+    segments: [usize; 2],
+    //静止时的Darboux向量 q0^-1 * q1
+    rest_darboux: Quaternion<f32>,
+    stiffness_inv: f32,
+    lambda: Vector3<f32>,
+}
+
+//Cosserat杆(Kugelstadt & Schömer 2016)，以XPBD形式求解拉伸剪切与弯曲扭转约束
+#[derive(Default)]
+pub struct Rods {
+    pub segments: Vec<RodSegment>,
+    bend_twists: Vec<BendTwistConstraint>,
+}
+
+//把a转到b的最小旋转，a与b反向时绕任意垂直轴旋转180度
+fn rotation_between(a: &Vector3<f32>, b: &Vector3<f32>) -> UnitQuaternion<f32> {
+    return UnitQuaternion::rotation_between(a, b).unwrap_or_else(|| {
+        let axis = match a.cross(&Vector3::x()).try_normalize(EPS) {
+            Some(axis) => axis,
+            None => a.cross(&Vector3::y()).normalize(),
+        };
+        UnitQuaternion::from_scaled_axis(axis * std::f32::consts::PI)
+    });
+}
+
+impl Rods {
+    //按indexes的顺序依次连接质点，返回新分段的索引范围。
+    //初始朝向沿切线方向平行移动，当前形状即为静止形状
+    pub fn add_rod(
+        &mut self,
+        particles_data: &[ParticleData],
+        indexes: &[usize],
+        stretch_stiffness_inv: f32,
+        bend_twist_stiffness_inv: f32,
+    ) -> Range<usize> {
+        let first = self.segments.len();
+        let mut orientation = UnitQuaternion::identity();
+        let mut prev_tangent = Vector3::z();
+        for pair in indexes.windows(2) {
+            let p0 = &particles_data[pair[0]];
+            let p1 = &particles_data[pair[1]];
+            let d = p1.position - p0.position;
+            let rest_length = d.norm();
+            let tangent = d.try_normalize(EPS).unwrap_or(prev_tangent);
+            orientation = rotation_between(&prev_tangent, &tangent) * orientation;
+            prev_tangent = tangent;
+            //与两端质点的质量同一量级的转动惯量
+            let wq = if rest_length > EPS {
+                (p0.w + p1.w) / (2.0 * rest_length * rest_length)
+            } else {
+                0.0
+            };
+            self.segments.push(RodSegment {
+                p_indexes: [pair[0], pair[1]],
+                orientation: orientation,
+                angular_velocity: Vector3::zeros(),
+                wq: wq,
+                rest_length: rest_length,
+                prev_orientation: orientation,
+                stiffness_inv: stretch_stiffness_inv,
+                lambda: Vector3::zeros(),
+            });
+        }
+        let last = self.segments.len();
+        for s in first..last.saturating_sub(1) {
+            let q0 = self.segments[s].orientation;
+            let q1 = self.segments[s + 1].orientation;
+            self.bend_twists.push(BendTwistConstraint {
+                segments: [s, s + 1],
+                rest_darboux: q0.conjugate().quaternion() * q1.quaternion(),
+                stiffness_inv: bend_twist_stiffness_inv,
+                lambda: Vector3::zeros(),
+            });
+        }
+        return first..last;
+    }
+
+    pub fn predict(&mut self, dt: f32) {
+        for s in self.segments.iter_mut() {
+            s.prev_orientation = s.orientation;
+            if s.wq == 0.0 {
+                continue;
+            }
+            let q = s.orientation.quaternion();
+            let dq = Quaternion::from_parts(0.0, s.angular_velocity * dt) * q * 0.5;
+            s.rotate(&dq);
+        }
+    }
+
+    fn solve_stretch_shear(particles_data: &mut [ParticleData], s: &mut RodSegment, alpha: f32) {
+        let [i0, i1] = s.p_indexes;
+        let (w0, w1) = (particles_data[i0].w, particles_data[i1].w);
+        let l = s.rest_length;
+        if l < EPS {
+            return;
+        }
+        let w = (w0 + w1) / (l * l) + 4.0 * s.wq;
+        if w + alpha < EPS {
+            return;
+        }
+        let p0 = particles_data[i0].predict_position;
+        let p1 = particles_data[i1].predict_position;
+        let gamma = (p1 - p0) / l - s.d3();
+        let d_lambda = -(gamma + s.lambda * alpha) / (w + alpha);
+        s.lambda += d_lambda;
+        particles_data[i0].predict_position -= d_lambda * (w0 / l);
+        particles_data[i1].predict_position += d_lambda * (w1 / l);
+        if s.wq > 0.0 {
+            //q * e3的共轭
+            let q_e3_bar = s.orientation.quaternion() * Quaternion::new(0.0, 0.0, 0.0, -1.0);
+            let dq = Quaternion::from_parts(0.0, d_lambda) * q_e3_bar * (-2.0 * s.wq);
+            s.rotate(&dq);
+        }
+    }
+
+    fn solve_bend_twist(segments: &mut [RodSegment], c: &mut BendTwistConstraint, alpha: f32) {
+        let [s0, s1] = c.segments;
+        let (wq0, wq1) = (segments[s0].wq, segments[s1].wq);
+        if wq0 + wq1 + alpha < EPS {
+            return;
+        }
+        let q0 = *segments[s0].orientation.quaternion();
+        let q1 = *segments[s1].orientation.quaternion();
+        let darboux = q0.conjugate() * q1;
+        //q与-q表示同一旋转，取与静止值更接近的一侧
+        let minus = darboux - c.rest_darboux;
+        let plus = darboux + c.rest_darboux;
+        let omega = if minus.norm_squared() > plus.norm_squared() {
+            plus.imag()
+        } else {
+            minus.imag()
+        };
+        let d_lambda = -(omega + c.lambda * alpha) / (wq0 + wq1 + alpha);
+        c.lambda += d_lambda;
+        let d_omega = Quaternion::from_parts(0.0, d_lambda);
+        segments[s0].rotate(&(q1 * d_omega * (-wq0)));
+        segments[s1].rotate(&(q0 * d_omega * wq1));
+    }
+
+    //每个子步开始求解前调用一次
+    pub fn begin_solve(&mut self) {
+        for s in self.segments.iter_mut() {
+            s.lambda = Vector3::zeros();
+        }
+        for c in self.bend_twists.iter_mut() {
+            c.lambda = Vector3::zeros();
+        }
+    }
+
+    //一次求解迭代，与质点约束在同一迭代循环中交替求解
+    pub fn solve_iteration(&mut self, particles_data: &mut [ParticleData], dt: f32) {
+        for s in self.segments.iter_mut() {
+            let alpha = s.stiffness_inv / (dt * dt);
+            Self::solve_stretch_shear(particles_data, s, alpha);
+        }
+        for c in self.bend_twists.iter_mut() {
+            let alpha = c.stiffness_inv / (dt * dt);
+            Self::solve_bend_twist(&mut self.segments, c, alpha);
+        }
+    }
+
+    pub fn update_velocities(&mut self, dt: f32) {
+        for s in self.segments.iter_mut() {
+            if s.wq == 0.0 {
+                continue;
+            }
+            let dq = s.orientation.quaternion() * s.prev_orientation.quaternion().conjugate();
+            let omega = dq.imag() * (2.0 / dt);
+            s.angular_velocity = if dq.w >= 0.0 { omega } else { -omega };
+        }
+    }
+
+    //撕裂时另一端位于normal正方向一侧的分段改为引用质点to
+    pub fn split_particle(
+        &mut self,
+        particles_data: &[ParticleData],
+        from: usize,
+        to: usize,
+        normal: &Vector3<f32>,
+    ) {
+        let origin = &particles_data[from].predict_position;
+        for s in self.segments.iter_mut() {
+            let other = match s.p_indexes {
+                [p0, p1] if p0 == from => p1,
+                [p0, p1] if p1 == from => p0,
+                _ => continue,
+            };
+            let others = std::iter::once(&particles_data[other].predict_position);
+            if !is_on_split_side(origin, others, normal) {
+                continue;
+            }
+            for p_index in s.p_indexes.iter_mut().filter(|p| **p == from) {
+                *p_index = to;
+            }
+        }
+    }
+
+    //引用被移除质点的分段，以及与这些分段相关的弯曲扭转约束一并移除
+    pub fn remap_particles(&mut self, remap: &[Option<usize>]) {
+        let mut segment_remap = Vec::with_capacity(self.segments.len());
+        let mut next = 0;
+        self.segments = std::mem::take(&mut self.segments)
+            .into_iter()
+            .filter_map(
+                |mut s| match (remap[s.p_indexes[0]], remap[s.p_indexes[1]]) {
+                    (Some(p0), Some(p1)) => {
+                        s.p_indexes = [p0, p1];
+                        segment_remap.push(Some(next));
+                        next += 1;
+                        Some(s)
+                    }
+                    _ => {
+                        segment_remap.push(None);
+                        None
+                    }
+                },
+            )
+            .collect();
+        self.bend_twists = std::mem::take(&mut self.bend_twists)
+            .into_iter()
+            .filter_map(|mut c| {
+                match (segment_remap[c.segments[0]], segment_remap[c.segments[1]]) {
+                    (Some(s0), Some(s1)) => {
+                        c.segments = [s0, s1];
+                        Some(c)
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+
+use nalgebra::distance_squared;
+
+use crate::{
+    collision::spatial_hash::SpatialHash,
+    collision_constraint::ParticleCollisionConstraint,
+    constraints::{constraint::ConstraintsData, particle::ParticleData, solver::ConstraintsSolver},
+};
+
+//基于空间哈希的质点自碰撞检测
+pub struct SelfCollision {
+    spatial_hash: SpatialHash,
+    //是否忽略由距离约束直接相连的质点对
+    exclude_connected: bool,
+    //由约束生成的质点对，约束增删或断裂后重新收集
+    connected_pairs: HashSet<(usize, usize)>,
+    //单独指定的质点对，例如撕裂时复制出的质点，以(小索引, 大索引)表示
+    excluded_pairs: HashSet<(usize, usize)>,
+    neighbors: Vec<usize>,
+}
+
+impl SelfCollision {
+    pub fn new(exclude_connected: bool) -> SelfCollision {
+        return SelfCollision {
+            spatial_hash: SpatialHash::new(),
+            exclude_connected: exclude_connected,
+            connected_pairs: HashSet::new(),
+            excluded_pairs: HashSet::new(),
+            neighbors: vec![],
+        };
+    }
+
+    //约束拓扑变化后调用
+    pub fn update_connected_pairs<T: ConstraintsSolver>(&mut self, constraints: &T) {
+        self.connected_pairs.clear();
+        if self.exclude_connected {
+            constraints.collect_connected_pairs(&mut self.connected_pairs);
+        }
+    }
+
+    pub fn exclude_pair(&mut self, p1: usize, p2: usize) {
+        self.excluded_pairs.insert((p1.min(p2), p1.max(p2)));
+    }
+
+    pub fn remap_particles(&mut self, remap: &[Option<usize>]) {
+        self.excluded_pairs = self
+            .excluded_pairs
+            .iter()
+            .filter_map(|(p1, p2)| match (remap[*p1], remap[*p2]) {
+                (Some(p1), Some(p2)) => Some((p1.min(p2), p1.max(p2))),
+                _ => None,
+            })
+            .collect();
+        self.connected_pairs.clear();
+    }
+
+    pub fn generate_collision_constraints(
+        &mut self,
+        particles_data: &[ParticleData],
+        collisions: &mut ConstraintsData<ParticleCollisionConstraint>,
+    ) {
+        collisions.clear();
+        let max_radius = particles_data
+            .iter()
+            .map(|p| p.radius)
+            .fold(0.0f32, f32::max);
+        if max_radius <= 0.0 {
+            return;
+        }
+        //格子边长取最大直径，相邻格子即可覆盖所有可能接触的质点
+        self.spatial_hash.build(
+            particles_data.iter().map(|p| &p.predict_position),
+            particles_data.len(),
+            2.0 * max_radius,
+        );
+        for (i, p) in particles_data.iter().enumerate() {
+            let neighbors = &mut self.neighbors;
+            neighbors.clear();
+            self.spatial_hash.query(&p.predict_position, |j| {
+                if j > i {
+                    neighbors.push(j);
+                }
+            });
+            //不同格子可能哈希到同一位置，需要去重
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            for &j in neighbors.iter() {
+                let q = &particles_data[j];
+                if p.w == 0.0 && q.w == 0.0 {
+                    continue;
+                }
+                let min_distance = p.radius + q.radius;
+                if distance_squared(&p.predict_position, &q.predict_position)
+                    >= min_distance * min_distance
+                {
+                    continue;
+                }
+                if self.connected_pairs.contains(&(i, j)) || self.excluded_pairs.contains(&(i, j)) {
+                    continue;
+                }
+                collisions.push(ParticleCollisionConstraint::new(i, j, min_distance));
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use nalgebra::{Point3, Vector3};
+
+use crate::constraints::constraint::ConstraintId;
+
+pub const NO_PARTICLE: u32 = u32::MAX;
+
+//约束断裂事件，group为约束在XPBD约束元组中的位置
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct BreakEvent {
+    pub group: u32,
+    pub id: ConstraintId,
+    //断裂约束的前两个质点，不足时为NO_PARTICLE
+    pub p1: u32,
+    pub p2: u32,
+    //撕裂时由p2复制出的新质点，未复制时为NO_PARTICLE
+    pub new_particle: u32,
+}
+
+//撕裂时以与被复制质点相连的其它点的中心判断所在一侧，位于normal正方向一侧的改为引用新质点
+pub fn is_on_split_side<'a, I>(origin: &Point3<f32>, others: I, normal: &Vector3<f32>) -> bool
+where
+    I: Iterator<Item = &'a Point3<f32>>,
+{
+    let mut center = Vector3::zeros();
+    for p in others {
+        center += p - origin;
+    }
+    return center.dot(normal) > 0.0;
+}
@@ -0,0 +1,145 @@
+use nalgebra::Vector3;
+
+use crate::{constraints::particle::ParticleData, tearing::is_on_split_side};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct WindSettings {
+    pub velocity: Vector3<f32>,
+    pub air_density: f32,
+    pub drag_coefficient: f32,
+    pub lift_coefficient: f32,
+    //湍流速度的幅值，为0时不启用
+    pub turbulence: f32,
+    pub turbulence_frequency: f32,
+}
+
+impl Default for WindSettings {
+    fn default() -> Self {
+        Self {
+            velocity: Vector3::zeros(),
+            air_density: 1.2,
+            drag_coefficient: 1.0,
+            lift_coefficient: 0.5,
+            turbulence: 0.0,
+            turbulence_frequency: 1.0,
+        }
+    }
+}
+
+//按三角形的朝向与面积计算空气阻力与升力，平均分配到三个顶点上
+#[derive(Default)]
+pub struct Wind {
+    pub settings: WindSettings,
+    triangles: Vec<[usize; 3]>,
+    time: f32,
+}
+
+impl Wind {
+    pub fn new(triangles: Vec<[usize; 3]>) -> Wind {
+        return Wind {
+            settings: Default::default(),
+            triangles: triangles,
+            time: 0.0,
+        };
+    }
+
+    //随位置与时间平滑变化的伪随机速度
+    fn turbulence(&self, p: &Vector3<f32>) -> Vector3<f32> {
+        let s = &self.settings;
+        if s.turbulence == 0.0 {
+            return Vector3::zeros();
+        }
+        let t = self.time * s.turbulence_frequency * std::f32::consts::TAU;
+        let noise = vector![
+            (p.dot(&vector![1.7, 0.9, 1.3]) + t).sin(),
+            (p.dot(&vector![0.8, 2.1, 1.1]) + t * 1.3 + 1.0).sin(),
+            (p.dot(&vector![1.2, 1.4, 0.7]) + t * 0.7 + 2.0).sin()
+        ];
+        return noise * s.turbulence;
+    }
+
+    //将风力累加到质点的external_force上
+    pub fn apply(&mut self, particles_data: &mut [ParticleData], dt: f32) {
+        self.time += dt;
+        if self.triangles.is_empty() {
+            return;
+        }
+        let s = self.settings;
+        for t in self.triangles.iter() {
+            let [p0, p1, p2] = [
+                &particles_data[t[0]],
+                &particles_data[t[1]],
+                &particles_data[t[2]],
+            ];
+            let cross = (p1.position - p0.position).cross(&(p2.position - p0.position));
+            let area = cross.norm() * 0.5;
+            let mut normal = match cross.try_normalize(f32::EPSILON) {
+                Some(n) => n,
+                None => continue,
+            };
+            let center = (p0.position.coords + p1.position.coords + p2.position.coords) / 3.0;
+            let velocity = (p0.velocity + p1.velocity + p2.velocity) / 3.0;
+            let relative = s.velocity + self.turbulence(&center) - velocity;
+            let speed = relative.norm();
+            if speed < f32::EPSILON {
+                continue;
+            }
+            let direction = relative / speed;
+            let mut cos = normal.dot(&direction);
+            if cos < 0.0 {
+                normal = -normal;
+                cos = -cos;
+            }
+            let pressure = 0.5 * s.air_density * area * speed * speed;
+            //阻力沿相对风速方向，与迎风面积成正比
+            let mut force = direction * (pressure * s.drag_coefficient * cos);
+            //升力垂直于相对风速，位于风速与法线所在的平面内
+            if let Some(lift_direction) = normal
+                .cross(&direction)
+                .cross(&direction)
+                .try_normalize(f32::EPSILON)
+            {
+                let sin = (1.0 - cos * cos).max(0.0).sqrt();
+                force -= lift_direction * (pressure * s.lift_coefficient * cos * sin);
+            }
+            for p_index in t {
+                particles_data[*p_index].external_force += force / 3.0;
+            }
+        }
+    }
+
+    //撕裂时位于normal正方向一侧的三角形改为引用质点to
+    pub fn split_particle(
+        &mut self,
+        particles_data: &[ParticleData],
+        from: usize,
+        to: usize,
+        normal: &Vector3<f32>,
+    ) {
+        let origin = &particles_data[from].predict_position;
+        for t in self.triangles.iter_mut() {
+            if !t.contains(&from) {
+                continue;
+            }
+            let others = t
+                .iter()
+                .filter(|p| **p != from)
+                .map(|p| &particles_data[*p].predict_position);
+            if !is_on_split_side(origin, others, normal) {
+                continue;
+            }
+            for p_index in t.iter_mut().filter(|p| **p == from) {
+                *p_index = to;
+            }
+        }
+    }
+
+    pub fn remap_particles(&mut self, remap: &[Option<usize>]) {
+        self.triangles = self
+            .triangles
+            .iter()
+            .filter_map(|t| Some([remap[t[0]]?, remap[t[1]]?, remap[t[2]]?]))
+            .collect();
+    }
+}
@@ -1,35 +1,74 @@
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 
 use nalgebra::{Point3, Vector3};
 
 use crate::{
     collision::{
+        collider::Pose,
         intersect::{ContactInfo, GetClosestSurfacePoint},
         shapes::ShapesManager,
     },
-    collision_constraint::CollisionConstraint,
+    collision_constraint::{CollisionConstraint, ParticleCollisionConstraint},
     constraints::{
-        constraint::{Constraint, ConstraintsData},
-        particle::{particle_flags, particle_flags_index, ParticleData, ParticlesDataBuilder},
+        constraint::{Constraint, ConstraintForce, ConstraintsData},
+        particle::{
+            particle_flags, particle_flags_index, ParticleData, ParticleForceKind,
+            ParticlesDataBuilder,
+        },
         solver::ConstraintsSolver,
     },
+    constraints_impl::tether_constraint::TetherConstraint,
+    damping::{Damping, DampingSettings},
+    force_field::{ForceField, ForceFields},
+    rigid_body::{RigidAttachment, RigidBodies, RigidBody, RigidBodyShape},
+    rod::{RodSegment, Rods},
+    self_collision::SelfCollision,
+    tearing::{BreakEvent, NO_PARTICLE},
+    wind::{Wind, WindSettings},
 };
 
 pub struct XPBD<T>
 where
     T: ConstraintsSolver,
 {
+    //创建时指定的固定时间步长
     dt: f32,
+    //最近一次update使用的时间步长，约束缓存按它计算
+    last_dt: f32,
     iterate_count: u32,
+    substep_count: u32,
     field_force: Vector3<f32>,
     field_acceleration: Vector3<f32>,
+    //随位置变化的力场，逐质点求值
+    force_fields: ForceFields,
     constraints: T,
+    //由attach集合与距离约束生成的长程连接约束
+    tethers: ConstraintsData<TetherConstraint>,
+    use_tethers: bool,
+    tether_stiffness_inv: f32,
+    //锚点或约束拓扑变化后，在下一次update开始时重新生成
+    is_tethers_dirty: bool,
     collision_constraints: ConstraintsData<CollisionConstraint>,
+    self_collision: Option<SelfCollision>,
+    self_collision_constraints: ConstraintsData<ParticleCollisionConstraint>,
     particles_data: Vec<ParticleData>,
+    rigid_bodies: RigidBodies,
+    //带朝向的Cosserat杆分段
+    rods: Rods,
     attached_particles: HashMap<usize, f32>,
     colliders: ShapesManager,
     bounciness: f32,
     dynamic_friction_factor: f32,
+    //约束断裂时是否复制质点使网格真正分开
+    split_on_break: bool,
+    //运行时新加入质点使用的半径
+    particle_radius: f32,
+    wind: Wind,
+    damping: Damping,
+    //断裂事件，多次update产生的事件累积到调用clear_break_events为止
+    break_events: Vec<BreakEvent>,
+    //约束增删、断裂或质点变化后，在下一次update开始时更新依赖约束拓扑的数据
+    is_topology_dirty: bool,
 }
 
 impl<T: ConstraintsSolver> XPBD<T> {
@@ -38,13 +77,17 @@ impl<T: ConstraintsSolver> XPBD<T> {
         dt: f32,
         field_force: &Vector3<f32>,
         acc: &Vector3<f32>,
+        force_fields: &ForceFields,
     ) {
         for p_data in particles_data {
             let flag = p_data.flag;
             let acc_scale = 1 - (flag & particle_flags::ATTACHED) >> particle_flags_index::ATTACHED;
+            let (local_force, local_acc) = force_fields.evaluate(&p_data.position);
+            let force = field_force + p_data.external_force + local_force;
+            let acc = acc + p_data.external_acceleration + local_acc;
             p_data.predict_position = p_data.position
                 + dt * p_data.velocity
-                + dt * dt * (field_force * p_data.w + acc * acc_scale as f32);
+                + dt * dt * (force * p_data.w + acc * acc_scale as f32);
         }
     }
 
@@ -58,6 +101,7 @@ impl<T: ConstraintsSolver> XPBD<T> {
                 p_index,
                 result.contact_position.clone(),
                 result.contact_normal.clone(),
+                result.contact_velocity.clone(),
             ));
         }
     }
@@ -69,11 +113,27 @@ impl<T: ConstraintsSolver> XPBD<T> {
         collisions.clear();
         for (p_index, p) in particles_data.iter().enumerate() {
             let pos = &p.predict_position;
-            for b in shapes.spheres.shapes() {
+            for b in shapes.spheres.colliders() {
                 let contact_info = b.get_closest_surface_point(&pos);
                 Self::try_add_collision_constraint(collisions, p_index, &contact_info);
             }
-            for s in shapes.infinite_planes.shapes() {
+            for c in shapes.capsules.colliders() {
+                let contact_info = c.get_closest_surface_point(&pos);
+                Self::try_add_collision_constraint(collisions, p_index, &contact_info);
+            }
+            for b in shapes.boxes.colliders() {
+                let contact_info = b.get_closest_surface_point(&pos);
+                Self::try_add_collision_constraint(collisions, p_index, &contact_info);
+            }
+            for s in shapes.infinite_planes.colliders() {
+                let contact_info = s.get_closest_surface_point(&pos);
+                Self::try_add_collision_constraint(collisions, p_index, &contact_info);
+            }
+            for m in shapes.triangle_meshes.colliders() {
+                let contact_info = m.get_closest_surface_point(&pos);
+                Self::try_add_collision_constraint(collisions, p_index, &contact_info);
+            }
+            for s in shapes.sdfs.colliders() {
                 let contact_info = s.get_closest_surface_point(&pos);
                 Self::try_add_collision_constraint(collisions, p_index, &contact_info);
             }
@@ -81,33 +141,119 @@ impl<T: ConstraintsSolver> XPBD<T> {
     }
 
     pub fn initialize(&mut self) {
+        let dt = self.substep_dt();
         let particles_data = &mut self.particles_data;
         self.constraints.initialize(particles_data);
-        self.constraints.calculate_cache(particles_data, self.dt);
+        self.constraints.calculate_cache(particles_data, dt);
     }
 
+    //最近一次update的子步时间步长，约束的alpha也按此步长计算
+    fn substep_dt(&self) -> f32 {
+        return self.last_dt / self.substep_count as f32;
+    }
+
+    //以创建时的固定dt更新
     pub fn update(&mut self) {
-        let dt = self.dt;
+        self.update_by(self.dt);
+    }
+
+    //以可变的时间步长更新一次，固定的dt保持不变，之后的update仍使用固定dt。
+    //速度以单位时间存储，因此不需要随dt缩放
+    pub fn update_with_dt(&mut self, dt: f32) {
+        //NaN、无穷大与非正数均被忽略
+        if !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        self.update_by(dt);
+    }
+
+    //将dt划分为substep_count个子步，每个子步都重新预测位置、生成碰撞并更新速度。
+    //dt与上一次不同时重新计算约束的alpha
+    fn update_by(&mut self, dt: f32) {
+        if dt != self.last_dt {
+            self.last_dt = dt;
+            self.constraints.mark_cache_dirty();
+            self.tethers.mark_cache_dirty();
+        }
+        if self.is_topology_dirty {
+            self.update_topology();
+        }
+        if self.is_tethers_dirty {
+            self.regenerate_tethers();
+        }
+        self.colliders.begin_update();
+        //风力按update开始时的速度计算，作用于本次update的所有子步
+        self.wind.apply(&mut self.particles_data, dt);
+        let dt = self.substep_dt();
+        for _ in 0..self.substep_count {
+            self.step(dt);
+        }
+        for p_data in self.particles_data.iter_mut() {
+            p_data.clear_external_forces();
+        }
+    }
+
+    fn step(&mut self, dt: f32) {
         let particles_data = &mut self.particles_data;
         let user_constraints = &mut self.constraints;
+        let tethers = &mut self.tethers;
         let collision_constraints = &mut self.collision_constraints;
+        let self_collision_constraints = &mut self.self_collision_constraints;
         let colliders = &self.colliders;
         //预测位置计算
         Self::calculate_predict_positions(
             particles_data,
-            self.dt,
+            dt,
             &self.field_force,
             &self.field_acceleration,
+            &self.force_fields,
         );
+        self.rigid_bodies
+            .predict(dt, &self.field_force, &self.field_acceleration);
+        self.rods.predict(dt);
         //碰撞检测，生成碰撞约束
         Self::generate_collision_constraints(particles_data, colliders, collision_constraints);
-        //约束求解
-        (user_constraints, collision_constraints).solve(particles_data, dt, self.iterate_count);
+        if let Some(self_collision) = &mut self.self_collision {
+            self_collision
+                .generate_collision_constraints(particles_data, self_collision_constraints);
+        }
+        self.rigid_bodies
+            .generate_contacts(particles_data, colliders);
+        //约束求解，刚体、杆与质点约束在同一迭代中交替求解，碰撞约束最后求解
+        let mut constraints = (user_constraints, tethers);
+        let mut collisions = (collision_constraints, self_collision_constraints);
+        constraints.begin_solve(particles_data, dt);
+        collisions.begin_solve(particles_data, dt);
+        self.rigid_bodies.begin_solve();
+        self.rods.begin_solve();
+        for _ in 0..self.iterate_count {
+            constraints.solve_iteration(particles_data);
+            self.rigid_bodies.solve_iteration(particles_data, dt);
+            self.rods.solve_iteration(particles_data, dt);
+            collisions.solve_iteration(particles_data);
+        }
+        self.rods.update_velocities(dt);
+        self.rigid_bodies.update_velocities(dt);
+        //移除超过断裂阈值的约束
+        let first_event = self.break_events.len();
+        self.constraints
+            .break_constraints(&self.particles_data, dt, 0, &mut self.break_events);
+        if self.break_events.len() > first_event {
+            self.is_topology_dirty = true;
+        }
+        if self.split_on_break {
+            self.split_broken_particles(first_event);
+        }
+        //断裂后的质点对在剩余子步中即可参与自碰撞
+        if self.is_topology_dirty {
+            self.update_topology();
+        }
         //更新速度与位置
-        for p_data in particles_data {
+        for p_data in self.particles_data.iter_mut() {
             p_data.velocity = (&p_data.predict_position - &p_data.position) / dt;
             p_data.position = p_data.predict_position;
         }
+        self.damping.apply(&mut self.particles_data, dt);
         //更新碰撞反馈
         for collision in self.collision_constraints.defines.iter() {
             let particle = &mut self.particles_data[collision.get_pindexes()[0]];
@@ -116,7 +262,8 @@ impl<T: ConstraintsSolver> XPBD<T> {
             if particle.w == 0.0 {
                 particle.velocity = Vector3::zeros();
             } else {
-                let velocity_in = particle.velocity;
+                //相对于碰撞体表面的速度
+                let velocity_in = particle.velocity - collision.concat_velocity;
                 //法线方向速度
                 let mut velocity_on_normal =
                     velocity_in.dot(&collision.concat_normal) * collision.concat_normal;
@@ -126,9 +273,201 @@ impl<T: ConstraintsSolver> XPBD<T> {
                 //偷懒直接系数百分比衰减
                 velocity_on_normal = -velocity_on_normal * self.bounciness;
                 velocity_on_tangent = velocity_on_tangent * (1.0 - self.dynamic_friction_factor);
-                particle.velocity = velocity_on_normal + velocity_on_tangent;
+                particle.velocity =
+                    velocity_on_normal + velocity_on_tangent + collision.concat_velocity;
             }
         }
+        self.colliders.advance(dt);
+    }
+
+    pub fn dt(&self) -> f32 {
+        return self.dt;
+    }
+
+    //运行时增删约束后调用，依赖约束拓扑的数据在下一次update开始时更新
+    pub fn mark_topology_dirty(&mut self) {
+        self.is_topology_dirty = true;
+    }
+
+    fn update_topology(&mut self) {
+        self.is_topology_dirty = false;
+        self.is_tethers_dirty = self.use_tethers;
+        if let Some(self_collision) = &mut self.self_collision {
+            self_collision.update_connected_pairs(&self.constraints);
+        }
+        self.damping.update_groups(
+            self.particles_data.len(),
+            &self.constraints,
+            self.rods.segments.iter().map(|s| s.p_indexes),
+        );
+    }
+
+    //断裂的约束两侧的质点若仍各自连接着约束，则复制第二个质点，
+    //使位于断裂约束一侧的约束改为引用新质点
+    fn split_broken_particles(&mut self, first_event: usize) {
+        for event in self.break_events[first_event..].iter_mut() {
+            if event.p2 == NO_PARTICLE {
+                continue;
+            }
+            let (p1, p2) = (event.p1 as usize, event.p2 as usize);
+            let normal = match (self.particles_data[p1].predict_position
+                - self.particles_data[p2].predict_position)
+                .try_normalize(f32::EPSILON)
+            {
+                Some(n) => n,
+                None => continue,
+            };
+            let new_index = self.particles_data.len();
+            let mut particle = self.particles_data[p2].clone();
+            //质量平分到两个质点上。被固定的质点保持固定，新质点不固定，使用固定前的质量
+            let attached_w = self.attached_particles.get_mut(&p2);
+            particle.w = match &attached_w {
+                Some(w) => **w * 2.0,
+                None => particle.w * 2.0,
+            };
+            particle.flag &= !particle_flags::ATTACHED;
+            self.particles_data.push(particle);
+            let particles_data = &self.particles_data;
+            let moved = self
+                .constraints
+                .split_particle(particles_data, p2, new_index, &normal);
+            if moved == 0 {
+                self.particles_data.pop();
+                continue;
+            }
+            match attached_w {
+                Some(w) => *w *= 2.0,
+                None => self.particles_data[p2].w *= 2.0,
+            }
+            let particles_data = &self.particles_data;
+            self.rigid_bodies
+                .split_particle(particles_data, p2, new_index, &normal);
+            self.rods
+                .split_particle(particles_data, p2, new_index, &normal);
+            self.wind
+                .split_particle(particles_data, p2, new_index, &normal);
+            if let Some(self_collision) = &mut self.self_collision {
+                self_collision.exclude_pair(p2, new_index);
+            }
+            event.new_particle = new_index as u32;
+        }
+    }
+
+    //group为约束在约束元组中的位置
+    pub fn constraint_count(&self, group: u32) -> usize {
+        return self.constraints.constraint_count(group);
+    }
+
+    //写入最近一个子步结束时各约束的约束力，返回写入的数量
+    pub fn copy_constraint_forces(&self, group: u32, forces: &mut [ConstraintForce]) -> usize {
+        return self
+            .constraints
+            .copy_forces(group, self.substep_dt(), forces);
+    }
+
+    pub fn break_events(&self) -> &[BreakEvent] {
+        return &self.break_events;
+    }
+
+    //读取断裂事件后调用
+    pub fn clear_break_events(&mut self) {
+        self.break_events.clear();
+    }
+
+    pub fn set_split_on_break(&mut self, split: bool) {
+        self.split_on_break = split;
+    }
+
+    //在末尾加入新质点，返回第一个新质点的索引
+    pub fn add_particles(
+        &mut self,
+        positions: Vec<Point3<f32>>,
+        masses: Vec<f32>,
+    ) -> Result<usize, &'static str> {
+        let first = self.particles_data.len();
+        let particles = ParticlesDataBuilder {
+            positions: positions,
+            masses: masses,
+            radii: vec![],
+            default_radius: self.particle_radius,
+            dampings: vec![],
+        }
+        .build()?;
+        self.particles_data.extend(particles);
+        return Ok(first);
+    }
+
+    //移除质点并压缩剩余质点，返回旧索引到新索引的映射表，被移除的质点为None。
+    //引用被移除质点的约束与刚体连接会一并移除
+    pub fn remove_particles(&mut self, indexes: &[usize]) -> Vec<Option<usize>> {
+        let count = self.particles_data.len();
+        let mut removed = vec![false; count];
+        for index in indexes.iter().filter(|i| **i < count) {
+            removed[*index] = true;
+        }
+        let mut remap = Vec::with_capacity(count);
+        let mut next = 0;
+        for r in removed.iter() {
+            if *r {
+                remap.push(None);
+            } else {
+                remap.push(Some(next));
+                next += 1;
+            }
+        }
+        let mut index = 0;
+        self.particles_data.retain(|_| {
+            index += 1;
+            !removed[index - 1]
+        });
+        self.constraints.remap_particles(&remap);
+        self.rigid_bodies.remap_particles(&remap);
+        self.rods.remap_particles(&remap);
+        self.wind.remap_particles(&remap);
+        if let Some(self_collision) = &mut self.self_collision {
+            self_collision.remap_particles(&remap);
+        }
+        //碰撞约束每个子步重新生成；断裂事件中的索引已失效，移除前需要先读取
+        self.collision_constraints.clear();
+        self.self_collision_constraints.clear();
+        self.break_events.clear();
+        self.attached_particles = self
+            .attached_particles
+            .drain()
+            .filter_map(|(p, w)| remap[p].map(|p| (p, w)))
+            .collect();
+        self.tethers.clear();
+        self.is_topology_dirty = true;
+        return remap;
+    }
+
+    //启用后每个自由质点到最近锚点的距离不超过沿距离约束的测地静止距离
+    pub fn set_tethers(&mut self, enabled: bool) {
+        self.use_tethers = enabled;
+        self.tethers.clear();
+        self.is_tethers_dirty = enabled;
+    }
+
+    pub fn tethers(&self) -> &ConstraintsData<TetherConstraint> {
+        return &self.tethers;
+    }
+
+    fn regenerate_tethers(&mut self) {
+        self.is_tethers_dirty = false;
+        let mut edges = vec![];
+        self.constraints.collect_rest_edges(&mut edges);
+        let anchors: Vec<usize> = self.attached_particles.keys().copied().collect();
+        self.tethers = ConstraintsData::from(TetherConstraint::generate(
+            &self.particles_data,
+            &anchors,
+            &edges,
+            self.tether_stiffness_inv,
+        ));
+    }
+
+    pub fn set_tether_stiffness_inv(&mut self, stiffness_inv: f32) {
+        self.tether_stiffness_inv = stiffness_inv;
+        self.is_tethers_dirty = self.use_tethers;
     }
 
     pub fn particles_count(&self) -> usize {
@@ -137,9 +476,10 @@ impl<T: ConstraintsSolver> XPBD<T> {
 
     pub fn attach(&mut self, particle_index: usize, position: Point3<f32>) {
         let particle = &mut self.particles_data[particle_index];
-        if !self.attached_particles.contains_key(&particle_index) {
-            self.attached_particles.insert(particle_index, particle.w);
+        if let Entry::Vacant(entry) = self.attached_particles.entry(particle_index) {
+            entry.insert(particle.w);
             particle.w = 0.;
+            self.is_tethers_dirty = self.use_tethers;
         }
         particle.position = position;
         particle.flag |= particle_flags::ATTACHED;
@@ -151,12 +491,126 @@ impl<T: ConstraintsSolver> XPBD<T> {
                 let particle = &mut self.particles_data[particle_index];
                 particle.w = original_w;
                 particle.flag &= !particle_flags::ATTACHED;
+                self.is_tethers_dirty = self.use_tethers;
                 return true;
             }
             _ => false,
         }
     }
 
+    pub fn add_rigid_body(&mut self, pose: Pose, mass: f32, shape: RigidBodyShape) -> usize {
+        self.rigid_bodies
+            .bodies
+            .push(RigidBody::new(pose, mass, shape));
+        return self.rigid_bodies.bodies.len() - 1;
+    }
+
+    //将质点连接到刚体局部空间中的local_point上，索引越界时返回None
+    pub fn attach_to_rigid_body(
+        &mut self,
+        particle_index: usize,
+        body_index: usize,
+        local_point: Point3<f32>,
+        stiffness_inv: f32,
+    ) -> Option<usize> {
+        if particle_index >= self.particles_data.len()
+            || body_index >= self.rigid_bodies.bodies.len()
+        {
+            return None;
+        }
+        self.rigid_bodies
+            .attachments
+            .push(Some(RigidAttachment::new(
+                particle_index,
+                body_index,
+                local_point,
+                stiffness_inv,
+            )));
+        return Some(self.rigid_bodies.attachments.len() - 1);
+    }
+
+    //以indexes的顺序连接质点构成一根杆，当前形状为静止形状，返回新分段的索引范围
+    pub fn add_rod(
+        &mut self,
+        indexes: &[usize],
+        stretch_stiffness_inv: f32,
+        bend_twist_stiffness_inv: f32,
+    ) -> Option<std::ops::Range<usize>> {
+        if indexes.iter().any(|i| *i >= self.particles_data.len()) {
+            return None;
+        }
+        self.is_topology_dirty = true;
+        return Some(self.rods.add_rod(
+            &self.particles_data,
+            indexes,
+            stretch_stiffness_inv,
+            bend_twist_stiffness_inv,
+        ));
+    }
+
+    pub fn rod_segments(&self) -> &[RodSegment] {
+        return &self.rods.segments;
+    }
+
+    //固定分段的朝向，例如头发的根部
+    pub fn set_rod_segment_fixed(&mut self, index: usize, fixed: bool) -> bool {
+        let particles_data = &self.particles_data;
+        return match self.rods.segments.get_mut(index) {
+            Some(segment) => {
+                segment.wq = if fixed {
+                    0.0
+                } else {
+                    let [p0, p1] = segment.p_indexes;
+                    let l = segment.rest_length.max(f32::EPSILON);
+                    (particles_data[p0].w + particles_data[p1].w) / (2.0 * l * l)
+                };
+                true
+            }
+            None => false,
+        };
+    }
+
+    pub fn rigid_body(&self, index: usize) -> &RigidBody {
+        return &self.rigid_bodies.bodies[index];
+    }
+
+    pub fn rigid_body_count(&self) -> usize {
+        return self.rigid_bodies.bodies.len();
+    }
+
+    //连接对应的质点被移除后返回None
+    pub fn rigid_attachment(&self, index: usize) -> Option<&RigidAttachment> {
+        return self.rigid_bodies.attachments.get(index)?.as_ref();
+    }
+
+    //瞬移刚体，速度清零，索引越界时返回false
+    pub fn set_rigid_body_pose(&mut self, index: usize, pose: Pose) -> bool {
+        return match self.rigid_bodies.bodies.get_mut(index) {
+            Some(body) => {
+                body.set_pose(pose);
+                true
+            }
+            None => false,
+        };
+    }
+
+    //设置刚体速度，运动学刚体在之后的update中按此速度运动，索引越界时返回false
+    pub fn set_rigid_body_velocity(
+        &mut self,
+        index: usize,
+        velocity: Vector3<f32>,
+        angular_velocity: Vector3<f32>,
+    ) -> bool {
+        return match self.rigid_bodies.bodies.get_mut(index) {
+            Some(body) => {
+                body.velocity = velocity;
+                body.angular_velocity = angular_velocity;
+                true
+            }
+            None => false,
+        };
+    }
+
     pub fn add_field_force(&mut self, force: Vector3<f32>) {
         self.field_force += force;
     }
@@ -165,6 +619,103 @@ impl<T: ConstraintsSolver> XPBD<T> {
         self.field_acceleration += acc;
     }
 
+    //直接覆盖，避免反复叠加相反值带来的浮点误差
+    pub fn set_field_force(&mut self, force: Vector3<f32>) {
+        self.field_force = force;
+    }
+
+    pub fn set_acceleration_field(&mut self, acc: Vector3<f32>) {
+        self.field_acceleration = acc;
+    }
+
+    //清空均匀力场与加速度场，不影响add_force_field加入的力场
+    pub fn clear_fields(&mut self) {
+        self.field_force = Vector3::zeros();
+        self.field_acceleration = Vector3::zeros();
+    }
+
+    //返回力场id，移除其它力场时不变；kind或falloff不合法时返回None
+    pub fn add_force_field(&mut self, field: ForceField) -> Option<usize> {
+        if !field.is_valid() {
+            return None;
+        }
+        return Some(self.force_fields.add(field));
+    }
+
+    //id不存在或kind、falloff不合法时返回false
+    pub fn set_force_field(&mut self, id: usize, field: ForceField) -> bool {
+        if !field.is_valid() {
+            return false;
+        }
+        return self.force_fields.set(id, field);
+    }
+
+    pub fn remove_force_field(&mut self, id: usize) -> bool {
+        return self.force_fields.remove(id);
+    }
+
+    pub fn clear_force_fields(&mut self) {
+        self.force_fields.clear();
+    }
+
+    //对单个质点施加力、加速度或冲量，力与加速度只作用于下一次update，索引越界时返回false
+    pub fn apply_particle_force(
+        &mut self,
+        kind: ParticleForceKind,
+        index: usize,
+        value: &Vector3<f32>,
+    ) -> bool {
+        return match self.particles_data.get_mut(index) {
+            Some(particle) => {
+                particle.apply(kind, value);
+                true
+            }
+            None => false,
+        };
+    }
+
+    pub fn apply_particle_forces(
+        &mut self,
+        kind: ParticleForceKind,
+        indexes: &[usize],
+        values: &[Vector3<f32>],
+    ) {
+        for (index, value) in indexes.iter().zip(values) {
+            self.apply_particle_force(kind, *index, value);
+        }
+    }
+
+    pub fn apply_particle_force_range(
+        &mut self,
+        kind: ParticleForceKind,
+        range: std::ops::Range<usize>,
+        value: &Vector3<f32>,
+    ) {
+        let end = range.end.min(self.particles_data.len());
+        for particle in self.particles_data[range.start.min(end)..end].iter_mut() {
+            particle.apply(kind, value);
+        }
+    }
+
+    pub fn set_damping(&mut self, settings: DampingSettings) {
+        self.damping.settings = settings;
+    }
+
+    //索引越界时返回false
+    pub fn set_particle_damping(&mut self, index: usize, damping: f32) -> bool {
+        return match self.particles_data.get_mut(index) {
+            Some(particle) => {
+                particle.damping = damping;
+                true
+            }
+            None => false,
+        };
+    }
+
+    pub fn set_wind(&mut self, settings: WindSettings) {
+        self.wind.settings = settings;
+    }
+
     pub fn field_force(&self) -> &Vector3<f32> {
         return &self.field_force;
     }
@@ -185,6 +736,7 @@ impl<T: ConstraintsSolver> XPBD<T> {
         return &self.constraints;
     }
 
+    //增删约束后需要调用mark_topology_dirty
     pub fn constraints_mut(&mut self) -> &mut T {
         return &mut self.constraints;
     }
@@ -209,54 +761,177 @@ where
     }
 }
 
-impl<T1, T2> ConstraintsSolverBuilder for (T1, T2)
-where
-    T1: ConstraintsSolverBuilder,
-    T2: ConstraintsSolverBuilder,
-{
-    type Type = (T1::Type, T2::Type);
-    fn build(self) -> Self::Type {
-        (self.0.build(), self.1.build())
-    }
+macro_rules! define_constraints_solver_builder {
+    ( $($head:ident,$index:tt),*)=>{
+        impl<$($head),*> ConstraintsSolverBuilder for ($($head),*)
+        where
+            $($head: ConstraintsSolverBuilder),*
+        {
+            type Type = ($($head::Type),*);
+            fn build(self) -> Self::Type {
+                ($(self.$index.build()),*)
+            }
+        }
+    };
 }
 
+define_constraints_solver_builder!(A, 0, B, 1);
+define_constraints_solver_builder!(A, 0, B, 1, C, 2);
+define_constraints_solver_builder!(A, 0, B, 1, C, 2, D, 3);
+define_constraints_solver_builder!(A, 0, B, 1, C, 2, D, 3, E, 4);
+define_constraints_solver_builder!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5);
+define_constraints_solver_builder!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6);
+define_constraints_solver_builder!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7);
+define_constraints_solver_builder!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8);
+
 pub struct XPBDBuilder<T>
 where
     T: ConstraintsSolverBuilder + Default,
 {
     pub dt: f32,
     pub iterate_count: u8,
+    //子步数量，每个子步执行iterate_count次迭代
+    pub substep_count: u8,
     pub constraints: T,
     pub positions: Vec<Point3<f32>>,
     pub masses: Vec<f32>,
     pub bounciness: f32,
     pub dynamic_friction_factor: f32,
+    //为空时所有质点使用particle_radius
+    pub particle_radii: Vec<f32>,
+    pub particle_radius: f32,
+    pub self_collision: bool,
+    //自碰撞是否忽略已由距离约束相连的质点对
+    pub self_collision_exclude_connected: bool,
+    //约束断裂时复制质点，使网格沿断裂处分开
+    pub split_on_break: bool,
+    //受风力作用的三角形
+    pub wind_triangles: Vec<[usize; 3]>,
+    //为attach的质点自动生成长程连接约束
+    pub tethers: bool,
+    pub tether_stiffness_inv: f32,
+    //速度更新后施加的阻尼
+    pub damping: DampingSettings,
+    //为空时所有质点只使用damping.linear
+    pub particle_dampings: Vec<f32>,
+    //每根杆按顺序排列的质点索引
+    pub rods: Vec<Vec<usize>>,
+    pub rod_stretch_stiffness_inv: f32,
+    pub rod_bend_twist_stiffness_inv: f32,
+}
+
+impl<T: ConstraintsSolverBuilder + Default> Default for XPBDBuilder<T> {
+    fn default() -> Self {
+        Self {
+            dt: 0.005,
+            iterate_count: 4,
+            substep_count: 1,
+            constraints: Default::default(),
+            positions: vec![],
+            masses: vec![],
+            bounciness: 1.0,
+            dynamic_friction_factor: 1.0,
+            particle_radii: vec![],
+            particle_radius: 0.0,
+            self_collision: false,
+            self_collision_exclude_connected: true,
+            split_on_break: false,
+            wind_triangles: vec![],
+            tethers: false,
+            tether_stiffness_inv: 0.0,
+            damping: Default::default(),
+            particle_dampings: vec![],
+            rods: vec![],
+            rod_stretch_stiffness_inv: 0.0,
+            rod_bend_twist_stiffness_inv: 0.0,
+        }
+    }
 }
 
 impl<T: ConstraintsSolverBuilder + Default> XPBDBuilder<T> {
-    pub fn build(&mut self) -> XPBD<T::Type> {
+    //参数不一致或索引越界时返回Err
+    pub fn build(&mut self) -> Result<XPBD<T::Type>, &'static str> {
         let particles_data = ParticlesDataBuilder {
             positions: std::mem::take(&mut self.positions),
             masses: std::mem::take(&mut self.masses),
+            radii: std::mem::take(&mut self.particle_radii),
+            default_radius: self.particle_radius,
+            dampings: std::mem::take(&mut self.particle_dampings),
+        }
+        .build()?;
+        let particle_count = particles_data.len();
+        if self
+            .wind_triangles
+            .iter()
+            .flatten()
+            .any(|&index| index >= particle_count)
+        {
+            return Err("wind triangle index out of range");
+        }
+        if self
+            .rods
+            .iter()
+            .flatten()
+            .any(|&index| index >= particle_count)
+        {
+            return Err("rod index out of range");
         }
-        .build()
-        .unwrap();
         let constraints = std::mem::take(&mut self.constraints);
         let constraints_data = constraints.build();
+        //约束在initialize中读取质点，越界的索引需要在此之前拒绝
+        let mut is_constraint_index_valid = true;
+        constraints_data.for_each_pindexes(&mut |p_indexes| {
+            if p_indexes.iter().any(|&index| index >= particle_count) {
+                is_constraint_index_valid = false;
+            }
+        });
+        if !is_constraint_index_valid {
+            return Err("constraint index out of range");
+        }
+        let self_collision = if self.self_collision {
+            Some(SelfCollision::new(self.self_collision_exclude_connected))
+        } else {
+            None
+        };
         let mut xpbd = XPBD {
             dt: self.dt,
+            last_dt: self.dt,
             iterate_count: (self.iterate_count as u32).max(1),
+            substep_count: (self.substep_count as u32).max(1),
             constraints: constraints_data,
+            tethers: Default::default(),
+            use_tethers: self.tethers,
+            tether_stiffness_inv: self.tether_stiffness_inv,
+            is_tethers_dirty: self.tethers,
             particles_data: particles_data,
+            rigid_bodies: Default::default(),
+            rods: Default::default(),
             collision_constraints: Default::default(),
+            self_collision: self_collision,
+            self_collision_constraints: Default::default(),
             field_force: Default::default(),
             attached_particles: Default::default(),
             colliders: Default::default(),
             field_acceleration: Default::default(),
+            force_fields: Default::default(),
             bounciness: self.bounciness,
             dynamic_friction_factor: self.dynamic_friction_factor,
+            split_on_break: self.split_on_break,
+            break_events: vec![],
+            particle_radius: self.particle_radius,
+            wind: Wind::new(std::mem::take(&mut self.wind_triangles)),
+            damping: Damping::new(self.damping),
+            is_topology_dirty: true,
         };
+        for indexes in std::mem::take(&mut self.rods) {
+            xpbd.rods.add_rod(
+                &xpbd.particles_data,
+                &indexes,
+                self.rod_stretch_stiffness_inv,
+                self.rod_bend_twist_stiffness_inv,
+            );
+        }
         xpbd.initialize();
-        return xpbd;
+        return Ok(xpbd);
     }
 }